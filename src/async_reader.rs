@@ -0,0 +1,298 @@
+use std::{io, path::Path};
+
+use futures_util::{stream, Stream};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, BufReader};
+
+use crate::{
+    ebcdic_syg::{BinaryHeader, EbcdicHeader, Trace, TraceHeader, TypedTrace},
+    errors::SegyError,
+    format::Endianness,
+    mapping::HeaderMapping,
+    reader::{
+        decode_textual_header, detect_endianness, parse_binary_header, parse_trace_header,
+        ExtendedTextualHeaders, TraceLayout,
+    },
+    samples::TraceSamples,
+    utils::IbmFloatPolicy,
+    value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
+};
+
+/// Async counterpart of `SegyReader` for tokio `AsyncRead + AsyncSeek` sources.
+///
+/// The file headers are read by `open`; traces are then read one at a time with
+/// `read_trace` or as a `Stream` with `traces`.
+pub struct AsyncSegyReader<R: AsyncRead + AsyncSeek + Unpin> {
+    reader: R,
+    /// Byte order used to decode binary values, detected from the binary header.
+    endianness: Endianness,
+    ebcdic_header: EbcdicHeader,
+    binary_header: BinaryHeader,
+    extended_textual_headers: Vec<EbcdicHeader>,
+    /// Byte offset of the first trace.
+    data_offset: u64,
+    /// Locations of the in-line, cross-line and CDP coordinate trace header fields.
+    header_mapping: HeaderMapping,
+    /// How IBM float samples outside the `f32` range are converted.
+    ibm_policy: IbmFloatPolicy,
+    /// Whether parsed trace headers keep their bytes in `TraceHeader::raw`.
+    keep_raw_trace_headers: bool,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncSegyReader<R> {
+    /// Reads the textual, binary and extended textual headers, leaving the source
+    /// positioned at the first trace.
+    pub async fn open(mut reader: R) -> Result<Self, SegyError> {
+        reader.seek(io::SeekFrom::Start(0)).await?;
+
+        let mut buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+        reader.read_exact(&mut buffer).await?;
+        let ebcdic_header = decode_textual_header(&buffer);
+
+        let mut buffer = vec![0u8; BINARY_HEADER_SIZE];
+        reader.read_exact(&mut buffer).await?;
+        let endianness = detect_endianness(&buffer);
+        let binary_header = parse_binary_header(&buffer, endianness)?;
+
+        let mut extended_textual_headers =
+            ExtendedTextualHeaders::new(binary_header.extended_textual_headers)?;
+        let mut buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+        while !extended_textual_headers.is_complete() {
+            reader.read_exact(&mut buffer).await?;
+            extended_textual_headers.push(&buffer);
+        }
+        let extended_textual_headers = extended_textual_headers.into_headers();
+
+        let layout = TraceLayout::from(&binary_header);
+        let data_offset = if layout.first_trace_offset > 0 {
+            reader
+                .seek(io::SeekFrom::Start(layout.first_trace_offset))
+                .await?
+        } else {
+            reader.stream_position().await?
+        };
+
+        Ok(AsyncSegyReader {
+            reader,
+            endianness,
+            ebcdic_header,
+            binary_header,
+            extended_textual_headers,
+            data_offset,
+            header_mapping: HeaderMapping::default(),
+            ibm_policy: IbmFloatPolicy::default(),
+            keep_raw_trace_headers: false,
+        })
+    }
+
+    /// Returns the textual header.
+    pub fn ebcdic_header(&self) -> &EbcdicHeader {
+        &self.ebcdic_header
+    }
+
+    /// Returns the binary header.
+    pub fn binary_header(&self) -> &BinaryHeader {
+        &self.binary_header
+    }
+
+    /// Returns the extended textual headers.
+    pub fn extended_textual_headers(&self) -> &[EbcdicHeader] {
+        &self.extended_textual_headers
+    }
+
+    /// Returns the byte order used to decode headers and samples.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Overrides the detected byte order.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Returns the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn header_mapping(&self) -> &HeaderMapping {
+        &self.header_mapping
+    }
+
+    /// Sets the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn set_header_mapping(&mut self, header_mapping: HeaderMapping) {
+        self.header_mapping = header_mapping;
+    }
+
+    /// Returns how IBM float samples outside the `f32` range are converted.
+    pub fn ibm_policy(&self) -> IbmFloatPolicy {
+        self.ibm_policy
+    }
+
+    /// Sets how IBM float samples outside the `f32` range are converted, e.g. to
+    /// flush denormals to zero or fail on overflow.
+    pub fn set_ibm_policy(&mut self, ibm_policy: IbmFloatPolicy) {
+        self.ibm_policy = ibm_policy;
+    }
+
+    /// Returns whether trace headers keep the bytes they were decoded from.
+    pub fn keep_raw_trace_headers(&self) -> bool {
+        self.keep_raw_trace_headers
+    }
+
+    /// Sets whether trace headers keep the 240 bytes they were decoded from in
+    /// `TraceHeader::raw`, so that `SegyWriter` writes back the bytes of every
+    /// field left unchanged. This costs an allocation per trace, so it is off by
+    /// default.
+    pub fn set_keep_raw_trace_headers(&mut self, keep_raw_trace_headers: bool) {
+        self.keep_raw_trace_headers = keep_raw_trace_headers;
+    }
+
+    /// Returns the byte offset of the first trace.
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    /// Seeks back to the first trace.
+    pub async fn rewind(&mut self) -> Result<(), SegyError> {
+        self.reader
+            .seek(io::SeekFrom::Start(self.data_offset))
+            .await?;
+        Ok(())
+    }
+
+    /// Reads the next trace header, returning `None` at the end of the file.
+    pub async fn read_trace_header(&mut self) -> Result<Option<TraceHeader>, SegyError> {
+        let mut buffer = [0u8; TRACE_HEADER_SIZE];
+        match self.reader.read_exact(&mut buffer).await {
+            Ok(_) => {
+                let mut header =
+                    parse_trace_header(&buffer, self.endianness, &self.header_mapping)?;
+                if self.keep_raw_trace_headers {
+                    header.raw = Some(Box::new(buffer));
+                }
+                Ok(Some(header))
+            }
+            // A partial header at the end of the file is treated as EOF, like `SegyReader`.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(SegyError::IoError(e)),
+        }
+    }
+
+    /// Reads the next trace, keeping the samples in their native type.
+    pub async fn read_typed_trace(&mut self) -> Result<Option<TypedTrace>, SegyError> {
+        let layout = TraceLayout::from(&self.binary_header);
+        let header = match self.read_trace_header().await? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let extensions_size = layout.extensions_size() as i64;
+        if extensions_size > 0 {
+            self.reader
+                .seek(io::SeekFrom::Current(extensions_size))
+                .await?;
+        }
+        let samples_in_trace = layout.samples_in_trace(&header) as usize;
+        let mut buffer = vec![0u8; samples_in_trace * layout.sample_format.sample_size()];
+        self.reader.read_exact(&mut buffer).await?;
+
+        let samples = TraceSamples::from_bytes_with_policy(
+            &buffer,
+            layout.sample_format,
+            self.endianness,
+            self.ibm_policy,
+        )?;
+        Ok(Some(TypedTrace { header, samples }))
+    }
+
+    /// Reads the next trace, returning `None` at the end of the file.
+    pub async fn read_trace(&mut self) -> Result<Option<Trace>, SegyError> {
+        Ok(self.read_typed_trace().await?.map(Trace::from))
+    }
+
+    /// Returns a stream of the traces from the current position until EOF, or
+    /// until the number of traces given by a rev2 binary header has been read.
+    ///
+    /// The stream ends after the first error rather than trying to resynchronize.
+    pub fn traces(&mut self) -> impl Stream<Item = Result<Trace, SegyError>> + '_ {
+        let layout = TraceLayout::from(&self.binary_header);
+        stream::try_unfold((self, 0u64), move |(reader, traces_read)| async move {
+            if layout.is_past_last_trace(traces_read) {
+                return Ok(None);
+            }
+            let trace = reader.read_trace().await?;
+            Ok(trace.map(|trace| (trace, (reader, traces_read + 1))))
+        })
+    }
+
+    /// Consumes the reader, returning a stream of the traces from the current
+    /// position until EOF, or until the number of traces given by a rev2 binary
+    /// header has been read.
+    pub fn into_traces(self) -> impl Stream<Item = Result<Trace, SegyError>> {
+        let layout = TraceLayout::from(&self.binary_header);
+        stream::try_unfold((self, 0u64), move |(mut reader, traces_read)| async move {
+            if layout.is_past_last_trace(traces_read) {
+                return Ok(None);
+            }
+            let trace = reader.read_trace().await?;
+            Ok(trace.map(|trace| (trace, (reader, traces_read + 1))))
+        })
+    }
+
+    /// Returns the underlying source.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Opens a SEG-Y file with tokio, reading its file headers up front.
+pub async fn open_segy_file_async<P: AsRef<Path>>(
+    path: P,
+) -> Result<AsyncSegyReader<BufReader<tokio::fs::File>>, SegyError> {
+    AsyncSegyReader::open(BufReader::new(tokio::fs::File::open(path).await?)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        reader::SegyReader,
+        testing::{junk_rev1_file, sample_traces, temp_file, write_file},
+    };
+
+    /// Reads every trace of `bytes` with the synchronous reader.
+    fn sync_traces(bytes: &[u8]) -> Vec<Trace> {
+        SegyReader::open(Cursor::new(bytes))
+            .unwrap()
+            .traces()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn cursor_traces_match_the_sync_reader() {
+        for bytes in [
+            write_file(SampleFormat::IeeeFloat, &sample_traces(7, 5)),
+            junk_rev1_file(&sample_traces(7, 5)),
+        ] {
+            let mut reader = AsyncSegyReader::open(Cursor::new(bytes.clone()))
+                .await
+                .unwrap();
+            assert_eq!(reader.data_offset(), 3600);
+            let traces: Vec<Trace> = reader.traces().try_collect().await.unwrap();
+            assert_eq!(traces.len(), 7);
+            assert_eq!(traces, sync_traces(&bytes));
+        }
+    }
+
+    #[tokio::test]
+    async fn file_traces_match_the_sync_reader() {
+        let bytes = write_file(SampleFormat::Int16, &sample_traces(4, 6));
+        let path = temp_file("async.sgy", &bytes);
+        let reader = open_segy_file_async(&path).await.unwrap();
+        let traces: Vec<Trace> = reader.into_traces().try_collect().await.unwrap();
+        assert_eq!(traces, sync_traces(&bytes));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::{
+    errors::SegyError,
+    format::SampleFormat,
+    reader::{SegyReader, TraceLayout},
+    utils::IbmNonFinite,
+    value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
+    writer::encode_binary_header,
+};
+
+/// What `convert_sample_format` wrote and which samples it had to change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// Number of traces converted.
+    pub traces: u64,
+    /// Number of samples converted.
+    pub samples: u64,
+    /// Samples outside the range of an integer or fixed point target format,
+    /// clamped to it.
+    pub clipped: u64,
+    /// Samples with a fractional part, rounded to the nearest integer of an
+    /// integer target format.
+    pub rounded: u64,
+}
+
+impl ConversionReport {
+    /// Returns true if no sample was clipped or rounded.
+    pub fn is_lossless(&self) -> bool {
+        self.clipped == 0 && self.rounded == 0
+    }
+}
+
+/// Copies the SEG-Y file opened by `reader` to `output`, rewriting the trace
+/// samples in the `target` sample format.
+///
+/// The data sample format code (bytes 3225-3226) is the only change to the
+/// binary header. The textual headers, trace headers, trace header extensions and
+/// everything after the last trace, such as the rev2 data trailer, are copied
+/// byte for byte, and everything keeps the byte order of the input. Samples are
+/// decoded with the reader's IBM float policy; NaN and infinities are saturated
+/// when the target is IBM float.
+///
+/// Traces are converted one at a time, so files of any size can be converted in
+/// constant memory. The reader must have been created with `SegyReader::open`.
+pub fn convert_sample_format<R: Read + Seek, W: Write>(
+    reader: &mut SegyReader<R>,
+    mut output: W,
+    target: SampleFormat,
+) -> Result<ConversionReport, SegyError> {
+    let (ebcdic_header, mut binary_header) = match (reader.ebcdic_header(), reader.binary_header())
+    {
+        (Some(ebcdic_header), Some(binary_header)) => {
+            (ebcdic_header.raw.clone(), binary_header.clone())
+        }
+        _ => {
+            return Err(SegyError::ParseError(
+                "File headers have not been read, use SegyReader::open".to_string(),
+            ))
+        }
+    };
+    let layout = TraceLayout::from(&binary_header);
+    let endianness = reader.endianness();
+
+    output.write_all(&ebcdic_header)?;
+    binary_header.sample_format_code = target;
+    output.write_all(&encode_binary_header(&binary_header)?)?;
+    let mut position = (EBCDIC_HEADER_SIZE + BINARY_HEADER_SIZE) as u64;
+    for header in reader.extended_textual_headers() {
+        output.write_all(&header.raw)?;
+        position += header.raw.len() as u64;
+    }
+
+    // Anything between the headers and a rev2 first trace offset is kept as is.
+    let trace_count = reader.trace_count()?;
+    let data_offset = reader.data_offset()?;
+    let source = reader.get_mut();
+    source.seek(SeekFrom::Start(position))?;
+    io::copy(
+        &mut source.take(data_offset.saturating_sub(position)),
+        &mut output,
+    )?;
+    source.seek(SeekFrom::Start(data_offset))?;
+
+    let mut report = ConversionReport::default();
+    // The trace header and its extensions are copied without being decoded.
+    let mut headers = vec![0u8; TRACE_HEADER_SIZE + layout.extensions_size()];
+    for _ in 0..trace_count {
+        reader.get_mut().read_exact(&mut headers)?;
+        let samples_in_trace = layout.samples_in_raw_trace(&headers, endianness)?;
+        let samples = reader.read_trace_samples(layout.sample_format, samples_in_trace)?;
+
+        let (clipped, rounded) = samples.integer_losses(target);
+        report.traces += 1;
+        report.samples += samples.len() as u64;
+        report.clipped += clipped as u64;
+        report.rounded += rounded as u64;
+
+        output.write_all(&headers)?;
+        output.write_all(&samples.to_bytes_with_policy(
+            target,
+            endianness,
+            IbmNonFinite::Saturate,
+        )?)?;
+    }
+
+    // The data trailer and any other bytes after the last trace are kept as is.
+    io::copy(reader.get_mut(), &mut output)?;
+    output.flush()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        ebcdic_syg::{BinaryHeader, Trace},
+        testing::{junk_rev1_file, sample_traces, write_file},
+        writer::{SegyRevision, SegyWriter},
+    };
+
+    /// Converts `bytes` to `target`, returning the report and the new file.
+    fn convert(bytes: Vec<u8>, target: SampleFormat) -> (ConversionReport, Vec<u8>) {
+        let mut reader = SegyReader::open(Cursor::new(bytes)).unwrap();
+        let mut output = Vec::new();
+        let report = convert_sample_format(&mut reader, &mut output, target).unwrap();
+        (report, output)
+    }
+
+    fn read_traces(bytes: &[u8]) -> Vec<Trace> {
+        SegyReader::open(Cursor::new(bytes))
+            .unwrap()
+            .traces()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn rev1_files_with_junk_in_rev2_fields_convert() {
+        let traces = sample_traces(5, 4);
+        let (report, output) = convert(junk_rev1_file(&traces), SampleFormat::IeeeFloat);
+        assert_eq!(report.traces, 5);
+        assert!(report.is_lossless());
+        let converted = read_traces(&output);
+        assert_eq!(converted.len(), 5);
+        for (converted, trace) in converted.iter().zip(&traces) {
+            assert_eq!(converted.data_samples, trace.data_samples);
+        }
+    }
+
+    #[test]
+    fn bytes_after_the_last_trace_are_copied() {
+        let mut bytes = write_file(SampleFormat::IeeeFloat, &sample_traces(3, 4));
+        bytes.extend_from_slice(b"not a trace");
+        let (report, output) = convert(bytes, SampleFormat::Int16);
+        assert_eq!(report.traces, 3);
+        assert!(output.ends_with(b"not a trace"));
+        assert_eq!(output.len(), 3600 + 3 * (240 + 8) + 11);
+    }
+
+    #[test]
+    fn rev2_data_trailers_are_copied() {
+        let mut writer = SegyWriter::new(Cursor::new(Vec::new()));
+        writer.set_revision(SegyRevision::Rev2);
+        writer.write_textual_header("C 1 REV2").unwrap();
+        writer
+            .write_binary_header(&BinaryHeader::new(SampleFormat::IbmFloat, 4, 2000))
+            .unwrap();
+        for trace in sample_traces(3, 4) {
+            writer.write_trace(&trace).unwrap();
+        }
+        writer.write_trailer_record("C TRAILER").unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        let (report, output) = convert(bytes, SampleFormat::IeeeFloat);
+        assert_eq!(report.traces, 3);
+        let mut reader = SegyReader::open(Cursor::new(output)).unwrap();
+        let trailer = reader.read_trailer_records().unwrap();
+        assert_eq!(trailer.len(), 1);
+        assert!(trailer[0].text.starts_with("C TRAILER"));
+    }
+
+    #[test]
+    fn fixed_point_clamps_are_reported() {
+        let mut traces = sample_traces(2, 4);
+        traces[0].data_samples = vec![40_000.0, -32_768.0, 0.001, f32::NAN];
+        traces[1].data_samples = vec![32_767.0, -40_000.0, 1.5, 0.0];
+        let (report, output) = convert(
+            write_file(SampleFormat::IeeeFloat, &traces),
+            SampleFormat::FixedPointWithGain,
+        );
+        assert_eq!(report.samples, 8);
+        assert_eq!(report.clipped, 3);
+        assert_eq!(
+            read_traces(&output)[1].data_samples[..3],
+            [32_767.0, -32_768.0, 1.5]
+        );
+    }
+}
@@ -1,11 +1,19 @@
-use crate::format::SampleFormat;
+use crate::{
+    format::{Endianness, SampleFormat},
+    geometry::coordinate_scale,
+    samples::TraceSamples,
+    value::{BINARY_HEADER_SIZE, TRACE_HEADER_SIZE},
+};
 
 /// Should support 40 lines of 80 characters.
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EbcdicHeader {
     /// The raw textual header (3200 characters).
     pub text: String,
+    /// The 3200 bytes the header was decoded from, empty for headers built in
+    /// memory. Writers reuse them while `text` is unchanged.
+    pub raw: Vec<u8>,
 }
 ///information from the SEG-Y binary header.
 #[derive(Debug, Clone)]
@@ -22,56 +30,617 @@ pub struct BinaryHeader {
     pub samples_per_trace: u16,
     /// Sample interval in microseconds (bytes 3217-3218).
     pub sample_interval_us: u16,
+    /// Number of data traces per ensemble (bytes 3213-3214).
+    pub data_traces_per_ensemble: u16,
+    /// Number of auxiliary traces per ensemble (bytes 3215-3216).
+    pub auxiliary_traces_per_ensemble: u16,
+    /// Sample interval of the original field recording in microseconds (bytes 3219-3220).
+    pub original_sample_interval_us: u16,
+    /// Number of samples per trace in the original field recording (bytes 3223-3224).
+    pub original_samples_per_trace: u16,
+    /// Ensemble fold (bytes 3227-3228).
+    pub ensemble_fold: u16,
+    /// Trace sorting code (bytes 3229-3230), e.g. 1 = as recorded, 2 = CDP ensemble.
+    pub trace_sorting_code: i16,
+    /// Vertical sum code (bytes 3231-3232), 1 = no sum, N = sum of N traces.
+    pub vertical_sum_code: u16,
+    /// Sweep frequency at start in Hz (bytes 3233-3234).
+    pub sweep_frequency_start_hz: u16,
+    /// Sweep frequency at end in Hz (bytes 3235-3236).
+    pub sweep_frequency_end_hz: u16,
+    /// Sweep length in milliseconds (bytes 3237-3238).
+    pub sweep_length_ms: u16,
+    /// Sweep type code (bytes 3239-3240), 1 = linear, 2 = parabolic, 3 = exponential.
+    pub sweep_type_code: u16,
+    /// Trace number of the sweep channel (bytes 3241-3242).
+    pub sweep_channel: u16,
+    /// Sweep trace taper length at start in milliseconds (bytes 3243-3244).
+    pub sweep_taper_start_ms: u16,
+    /// Sweep trace taper length at end in milliseconds (bytes 3245-3246).
+    pub sweep_taper_end_ms: u16,
+    /// Taper type (bytes 3247-3248), 1 = linear, 2 = cos², 3 = other.
+    pub taper_type: u16,
+    /// Correlated data traces (bytes 3249-3250), 1 = no, 2 = yes.
+    pub correlated_data_traces: u16,
+    /// Binary gain recovered (bytes 3251-3252), 1 = yes, 2 = no.
+    pub binary_gain_recovered: u16,
+    /// Amplitude recovery method (bytes 3253-3254).
+    pub amplitude_recovery_method: u16,
+    /// Measurement system (bytes 3255-3256), 1 = meters, 2 = feet.
+    pub measurement_system: u16,
+    /// Impulse signal polarity (bytes 3257-3258).
+    pub impulse_signal_polarity: u16,
+    /// Vibratory polarity code (bytes 3259-3260).
+    pub vibratory_polarity_code: u16,
+    /// Extended number of data traces per ensemble (bytes 3261-3264, rev2).
+    pub extended_data_traces_per_ensemble: u32,
+    /// Extended number of auxiliary traces per ensemble (bytes 3265-3268, rev2).
+    pub extended_auxiliary_traces_per_ensemble: u32,
+    /// Extended number of samples per trace (bytes 3269-3272, rev2).
+    pub extended_samples_per_trace: u32,
+    /// Extended sample interval (bytes 3273-3280, rev2).
+    pub extended_sample_interval: f64,
+    /// Extended sample interval of the original field recording (bytes 3281-3288, rev2).
+    pub extended_original_sample_interval: f64,
+    /// Extended number of samples per trace in the original recording (bytes 3289-3292, rev2).
+    pub extended_original_samples_per_trace: u32,
+    /// Extended ensemble fold (bytes 3293-3296, rev2).
+    pub extended_ensemble_fold: u32,
+    /// Byte order constant (bytes 3297-3300, rev2), 0x01020304 when present.
+    pub byte_order_constant: u32,
+    /// Major SEG-Y format revision number (byte 3501).
+    pub segy_revision_major: u8,
+    /// Minor SEG-Y format revision number (byte 3502).
+    pub segy_revision_minor: u8,
+    /// Fixed length trace flag (bytes 3503-3504), 1 = all traces have the same length.
+    pub fixed_length_traces: u16,
+    /// Number of 3200-byte extended textual file headers (bytes 3505-3506),
+    /// -1 means a variable number terminated by an `((SEG: EndText))` stanza.
+    pub extended_textual_headers: i16,
+    /// Maximum number of additional 240-byte trace headers (bytes 3507-3510, rev2).
+    pub max_additional_trace_headers: u32,
+    /// Time basis code (bytes 3511-3512, rev2).
+    pub time_basis_code: u16,
+    /// Number of traces in this file (bytes 3513-3520, rev2).
+    pub traces_in_file: u64,
+    /// Byte offset of the first trace relative to the start of the file (bytes 3521-3528, rev2).
+    pub first_trace_offset: u64,
+    /// Number of 3200-byte data trailer stanza records (bytes 3529-3532, rev2).
+    pub trailer_stanza_records: i32,
+    /// Byte order detected for this file, used for all binary values.
+    pub endianness: Endianness,
+    /// The 400 bytes the header was decoded from, `None` for headers built in
+    /// memory. Writers keep the bytes of every field that was not changed.
+    ///
+    /// It is not compared by `==`.
+    pub raw: Option<Box<[u8; BINARY_HEADER_SIZE]>>,
 }
 
-/// Trace header.
+impl BinaryHeader {
+    /// Creates a big-endian SEG-Y rev1 binary header for fixed-length traces of
+    /// `samples_per_trace` samples in `sample_format`, `sample_interval_us`
+    /// microseconds apart.
+    ///
+    /// Every other field is zero and there are no raw bytes, so a writer encodes
+    /// every field.
+    pub fn new(
+        sample_format: SampleFormat,
+        samples_per_trace: u16,
+        sample_interval_us: u16,
+    ) -> Self {
+        BinaryHeader {
+            sample_format_code: sample_format,
+            samples_per_trace,
+            sample_interval_us,
+            job_id: 0,
+            line_number: 0,
+            reel_number: 0,
+            data_traces_per_ensemble: 0,
+            auxiliary_traces_per_ensemble: 0,
+            original_sample_interval_us: 0,
+            original_samples_per_trace: 0,
+            ensemble_fold: 0,
+            trace_sorting_code: 0,
+            vertical_sum_code: 0,
+            sweep_frequency_start_hz: 0,
+            sweep_frequency_end_hz: 0,
+            sweep_length_ms: 0,
+            sweep_type_code: 0,
+            sweep_channel: 0,
+            sweep_taper_start_ms: 0,
+            sweep_taper_end_ms: 0,
+            taper_type: 0,
+            correlated_data_traces: 0,
+            binary_gain_recovered: 0,
+            amplitude_recovery_method: 0,
+            measurement_system: 0,
+            impulse_signal_polarity: 0,
+            vibratory_polarity_code: 0,
+            extended_data_traces_per_ensemble: 0,
+            extended_auxiliary_traces_per_ensemble: 0,
+            extended_samples_per_trace: 0,
+            extended_sample_interval: 0.0,
+            extended_original_sample_interval: 0.0,
+            extended_original_samples_per_trace: 0,
+            extended_ensemble_fold: 0,
+            byte_order_constant: 0,
+            segy_revision_major: 1,
+            segy_revision_minor: 0,
+            fixed_length_traces: 1,
+            extended_textual_headers: 0,
+            max_additional_trace_headers: 0,
+            time_basis_code: 0,
+            traces_in_file: 0,
+            first_trace_offset: 0,
+            trailer_stanza_records: 0,
+            endianness: Endianness::Big,
+            raw: None,
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
+/// The standard 240-byte trace header.
+
+#[derive(Debug, Clone, Default)]
 pub struct TraceHeader {
     /// Trace sequence number within line (bytes 1-4).
     pub trace_sequence_line: i32,
+    /// Trace sequence number within SEG-Y file (bytes 5-8).
+    pub trace_sequence_file: i32,
     /// Original field record number (bytes 9-12).
     pub field_record_number: i32,
     /// Trace number within original field record (bytes 13-16).
     pub trace_number: i32,
     /// Energy source point number (bytes 17-20).
     pub source_point_number: i32,
-    /// Number of samples in this trace (bytes 115-116).
-    pub trace_sample_count: u16,
-    /// Sample interval in microseconds (bytes 117-118).
-    pub trace_sample_interval_us: u16,
-    // TODO 240-byte trace header
-    pub trace_sequence_file: i32,
     /// Ensemble (or "CDP") number (bytes 21-24).
     pub ensemble_number: i32,
     /// Trace number within the ensemble (bytes 25-28).
     pub trace_in_ensemble: i32,
+    /// Trace identification code, e.g. 1 = time-domain seismic data (bytes 29-30).
+    pub trace_identification_code: i16,
+    /// Number of vertically summed traces yielding this trace (bytes 31-32).
+    pub vertically_summed_traces: i16,
+    /// Number of horizontally stacked traces yielding this trace (bytes 33-34).
+    pub horizontally_stacked_traces: i16,
+    /// Data use, 1 = production, 2 = test (bytes 35-36).
+    pub data_use: i16,
     /// Distance from source point (offset) in meters/feet (bytes 37-40).
     pub offset: i32,
-    /// CDP X coordinate (bytes 73-76) — usage can vary by convention.
-    pub cdp_x: i32,
-    /// CDP Y coordinate (bytes 77-80) — usage can vary by convention.
-    pub cdp_y: i32,
-    /// Coordinate scalar (bytes 71-72) — apply to cdp_x & cdp_y if nonzero.
+    /// Receiver group elevation (bytes 41-44).
+    pub receiver_group_elevation: i32,
+    /// Surface elevation at source (bytes 45-48).
+    pub source_surface_elevation: i32,
+    /// Source depth below surface (bytes 49-52).
+    pub source_depth: i32,
+    /// Datum elevation at receiver group (bytes 53-56).
+    pub receiver_datum_elevation: i32,
+    /// Datum elevation at source (bytes 57-60).
+    pub source_datum_elevation: i32,
+    /// Water depth at source (bytes 61-64).
+    pub source_water_depth: i32,
+    /// Water depth at receiver group (bytes 65-68).
+    pub group_water_depth: i32,
+    /// Scalar applied to all elevations and depths in bytes 41-68 (bytes 69-70).
+    pub elevation_scalar: i16,
+    /// Coordinate scalar — apply to all coordinates if nonzero (bytes 71-72).
     pub coord_scalar: i16,
-    /// Year data recorded (bytes 159-160) — optional field in SEG-Y.
+    /// Source coordinate X (bytes 73-76).
+    pub source_x: i32,
+    /// Source coordinate Y (bytes 77-80).
+    pub source_y: i32,
+    /// Receiver group coordinate X (bytes 81-84).
+    pub group_x: i32,
+    /// Receiver group coordinate Y (bytes 85-88).
+    pub group_y: i32,
+    /// Coordinate units, 1 = length, 2 = arc seconds, 3 = degrees, 4 = DMS (bytes 89-90).
+    pub coordinate_units: i16,
+    /// Weathering velocity (bytes 91-92).
+    pub weathering_velocity: i16,
+    /// Subweathering velocity (bytes 93-94).
+    pub subweathering_velocity: i16,
+    /// Uphole time at source in milliseconds (bytes 95-96).
+    pub source_uphole_time_ms: i16,
+    /// Uphole time at receiver group in milliseconds (bytes 97-98).
+    pub group_uphole_time_ms: i16,
+    /// Source static correction in milliseconds (bytes 99-100).
+    pub source_static_correction_ms: i16,
+    /// Receiver group static correction in milliseconds (bytes 101-102).
+    pub group_static_correction_ms: i16,
+    /// Total static applied in milliseconds (bytes 103-104).
+    pub total_static_applied_ms: i16,
+    /// Lag time A in milliseconds (bytes 105-106).
+    pub lag_time_a_ms: i16,
+    /// Lag time B in milliseconds (bytes 107-108).
+    pub lag_time_b_ms: i16,
+    /// Delay recording time in milliseconds (bytes 109-110).
+    pub delay_recording_time_ms: i16,
+    /// Mute time start in milliseconds (bytes 111-112).
+    pub mute_time_start_ms: i16,
+    /// Mute time end in milliseconds (bytes 113-114).
+    pub mute_time_end_ms: i16,
+    /// Number of samples in this trace (bytes 115-116).
+    pub trace_sample_count: u16,
+    /// Sample interval in microseconds (bytes 117-118).
+    pub trace_sample_interval_us: u16,
+    /// Gain type of field instruments, 1 = fixed, 2 = binary, 3 = floating point (bytes 119-120).
+    pub gain_type: i16,
+    /// Instrument gain constant in dB (bytes 121-122).
+    pub instrument_gain_constant_db: i16,
+    /// Instrument early or initial gain in dB (bytes 123-124).
+    pub instrument_early_gain_db: i16,
+    /// Correlated, 1 = no, 2 = yes (bytes 125-126).
+    pub correlated: i16,
+    /// Sweep frequency at start in Hz (bytes 127-128).
+    pub sweep_frequency_start_hz: i16,
+    /// Sweep frequency at end in Hz (bytes 129-130).
+    pub sweep_frequency_end_hz: i16,
+    /// Sweep length in milliseconds (bytes 131-132).
+    pub sweep_length_ms: i16,
+    /// Sweep type, 1 = linear, 2 = parabolic, 3 = exponential, 4 = other (bytes 133-134).
+    pub sweep_type: i16,
+    /// Sweep trace taper length at start in milliseconds (bytes 135-136).
+    pub sweep_taper_start_ms: i16,
+    /// Sweep trace taper length at end in milliseconds (bytes 137-138).
+    pub sweep_taper_end_ms: i16,
+    /// Taper type, 1 = linear, 2 = cos², 3 = other (bytes 139-140).
+    pub taper_type: i16,
+    /// Alias filter frequency in Hz (bytes 141-142).
+    pub alias_filter_frequency_hz: i16,
+    /// Alias filter slope in dB/octave (bytes 143-144).
+    pub alias_filter_slope: i16,
+    /// Notch filter frequency in Hz (bytes 145-146).
+    pub notch_filter_frequency_hz: i16,
+    /// Notch filter slope in dB/octave (bytes 147-148).
+    pub notch_filter_slope: i16,
+    /// Low-cut frequency in Hz (bytes 149-150).
+    pub low_cut_frequency_hz: i16,
+    /// High-cut frequency in Hz (bytes 151-152).
+    pub high_cut_frequency_hz: i16,
+    /// Low-cut slope in dB/octave (bytes 153-154).
+    pub low_cut_slope: i16,
+    /// High-cut slope in dB/octave (bytes 155-156).
+    pub high_cut_slope: i16,
+    /// Year data recorded (bytes 157-158).
     pub year_data_recorded: u16,
-    /// Day of year (bytes 161-162).
+    /// Day of year (bytes 159-160).
     pub day_of_year: u16,
-    /// Hour of day (bytes 163-164).
+    /// Hour of day (bytes 161-162).
     pub hour_of_day: u16,
-    /// Minute of hour (bytes 165-166).
+    /// Minute of hour (bytes 163-164).
     pub minute_of_hour: u16,
-    /// Second of minute (bytes 167-168).
+    /// Second of minute (bytes 165-166).
     pub second_of_minute: u16,
+    /// Time basis code, 1 = local, 2 = GMT, 3 = other, 4 = UTC (bytes 167-168).
+    pub time_basis_code: i16,
+    /// Trace weighting factor, defined as 2^-N volts for the least significant bit (bytes 169-170).
+    pub trace_weighting_factor: i16,
+    /// Geophone group number of roll switch position one (bytes 171-172).
+    pub geophone_group_roll_switch: i16,
+    /// Geophone group number of trace number one within the original field record (bytes 173-174).
+    pub geophone_group_first_trace: i16,
+    /// Geophone group number of the last trace within the original field record (bytes 175-176).
+    pub geophone_group_last_trace: i16,
+    /// Gap size (total number of groups dropped) (bytes 177-178).
+    pub gap_size: i16,
+    /// Over travel associated with taper, 1 = down/behind, 2 = up/ahead (bytes 179-180).
+    pub over_travel: i16,
+    /// X coordinate of ensemble (CDP) position (bytes 181-184 unless remapped by `HeaderMapping`).
+    pub cdp_x: i32,
+    /// Y coordinate of ensemble (CDP) position (bytes 185-188 unless remapped by `HeaderMapping`).
+    pub cdp_y: i32,
+    /// In-line number for 3D poststack data (bytes 189-192 unless remapped by `HeaderMapping`).
+    pub inline_number: i32,
+    /// Cross-line number for 3D poststack data (bytes 193-196 unless remapped by `HeaderMapping`).
+    pub crossline_number: i32,
+    /// Shotpoint number (bytes 197-200).
+    pub shotpoint_number: i32,
+    /// Scalar applied to the shotpoint number (bytes 201-202).
+    pub shotpoint_scalar: i16,
+    /// Trace value measurement unit, e.g. 0 = unknown, 1 = pascal, 2 = volts (bytes 203-204).
+    pub trace_value_measurement_unit: i16,
+    /// Transduction constant mantissa (bytes 205-208).
+    pub transduction_constant_mantissa: i32,
+    /// Transduction constant power of ten exponent (bytes 209-210).
+    pub transduction_constant_exponent: i16,
+    /// Transduction units (bytes 211-212).
+    pub transduction_units: i16,
+    /// Device/trace identifier (bytes 213-214).
+    pub device_identifier: i16,
+    /// Scalar applied to times in bytes 95-114 (bytes 215-216).
+    pub time_scalar: i16,
+    /// Source type/orientation (bytes 217-218).
+    pub source_type_orientation: i16,
+    /// Source energy direction mantissa, in tenths of degrees (bytes 219-222).
+    pub source_energy_direction_mantissa: i32,
+    /// Source energy direction power of ten exponent (bytes 223-224).
+    pub source_energy_direction_exponent: i16,
+    /// Source measurement mantissa (bytes 225-228).
+    pub source_measurement_mantissa: i32,
+    /// Source measurement power of ten exponent (bytes 229-230).
+    pub source_measurement_exponent: i16,
+    /// Source measurement unit (bytes 231-232).
+    pub source_measurement_unit: i16,
+    /// The 240 bytes the header was decoded from, when the reader was asked to
+    /// keep them, e.g. with `SegyReader::set_keep_raw_trace_headers`. Writers keep
+    /// the bytes of every field that was not changed, including unassigned bytes
+    /// 233-240.
+    ///
+    /// It is not compared by `==`.
+    pub raw: Option<Box<[u8; TRACE_HEADER_SIZE]>>,
+}
+
+/// Implements `PartialEq` for a header by comparing every field but `raw`, so a
+/// header built in memory equals the same header read back from a file. The
+/// destructuring stops the build if a field is added without being listed.
+macro_rules! partial_eq_without_raw {
+    ($header:ident { $($field:ident,)* }) => {
+        impl PartialEq for $header {
+            fn eq(&self, other: &Self) -> bool {
+                let $header { $($field,)* raw: _ } = self;
+                $(*$field == other.$field)&&*
+            }
+        }
+    };
+}
+
+partial_eq_without_raw!(BinaryHeader {
+    job_id,
+    line_number,
+    reel_number,
+    sample_format_code,
+    samples_per_trace,
+    sample_interval_us,
+    data_traces_per_ensemble,
+    auxiliary_traces_per_ensemble,
+    original_sample_interval_us,
+    original_samples_per_trace,
+    ensemble_fold,
+    trace_sorting_code,
+    vertical_sum_code,
+    sweep_frequency_start_hz,
+    sweep_frequency_end_hz,
+    sweep_length_ms,
+    sweep_type_code,
+    sweep_channel,
+    sweep_taper_start_ms,
+    sweep_taper_end_ms,
+    taper_type,
+    correlated_data_traces,
+    binary_gain_recovered,
+    amplitude_recovery_method,
+    measurement_system,
+    impulse_signal_polarity,
+    vibratory_polarity_code,
+    extended_data_traces_per_ensemble,
+    extended_auxiliary_traces_per_ensemble,
+    extended_samples_per_trace,
+    extended_sample_interval,
+    extended_original_sample_interval,
+    extended_original_samples_per_trace,
+    extended_ensemble_fold,
+    byte_order_constant,
+    segy_revision_major,
+    segy_revision_minor,
+    fixed_length_traces,
+    extended_textual_headers,
+    max_additional_trace_headers,
+    time_basis_code,
+    traces_in_file,
+    first_trace_offset,
+    trailer_stanza_records,
+    endianness,
+});
+
+partial_eq_without_raw!(TraceHeader {
+    trace_sequence_line,
+    trace_sequence_file,
+    field_record_number,
+    trace_number,
+    source_point_number,
+    ensemble_number,
+    trace_in_ensemble,
+    trace_identification_code,
+    vertically_summed_traces,
+    horizontally_stacked_traces,
+    data_use,
+    offset,
+    receiver_group_elevation,
+    source_surface_elevation,
+    source_depth,
+    receiver_datum_elevation,
+    source_datum_elevation,
+    source_water_depth,
+    group_water_depth,
+    elevation_scalar,
+    coord_scalar,
+    source_x,
+    source_y,
+    group_x,
+    group_y,
+    coordinate_units,
+    weathering_velocity,
+    subweathering_velocity,
+    source_uphole_time_ms,
+    group_uphole_time_ms,
+    source_static_correction_ms,
+    group_static_correction_ms,
+    total_static_applied_ms,
+    lag_time_a_ms,
+    lag_time_b_ms,
+    delay_recording_time_ms,
+    mute_time_start_ms,
+    mute_time_end_ms,
+    trace_sample_count,
+    trace_sample_interval_us,
+    gain_type,
+    instrument_gain_constant_db,
+    instrument_early_gain_db,
+    correlated,
+    sweep_frequency_start_hz,
+    sweep_frequency_end_hz,
+    sweep_length_ms,
+    sweep_type,
+    sweep_taper_start_ms,
+    sweep_taper_end_ms,
+    taper_type,
+    alias_filter_frequency_hz,
+    alias_filter_slope,
+    notch_filter_frequency_hz,
+    notch_filter_slope,
+    low_cut_frequency_hz,
+    high_cut_frequency_hz,
+    low_cut_slope,
+    high_cut_slope,
+    year_data_recorded,
+    day_of_year,
+    hour_of_day,
+    minute_of_hour,
+    second_of_minute,
+    time_basis_code,
+    trace_weighting_factor,
+    geophone_group_roll_switch,
+    geophone_group_first_trace,
+    geophone_group_last_trace,
+    gap_size,
+    over_travel,
+    cdp_x,
+    cdp_y,
+    inline_number,
+    crossline_number,
+    shotpoint_number,
+    shotpoint_scalar,
+    trace_value_measurement_unit,
+    transduction_constant_mantissa,
+    transduction_constant_exponent,
+    transduction_units,
+    device_identifier,
+    time_scalar,
+    source_type_orientation,
+    source_energy_direction_mantissa,
+    source_energy_direction_exponent,
+    source_measurement_mantissa,
+    source_measurement_exponent,
+    source_measurement_unit,
+});
+
+/// SEG-Y rev2 trace header extension 1, the first additional 240-byte header
+/// that follows the standard trace header when the binary header announces
+/// additional trace headers (bytes 3507-3510).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceHeaderExtension1 {
+    /// Extended trace sequence number within line (bytes 1-8).
+    pub trace_sequence_line: u64,
+    /// Extended trace sequence number within the SEG-Y file (bytes 9-16).
+    pub trace_sequence_file: u64,
+    /// Extended original field record number (bytes 17-24).
+    pub field_record_number: i64,
+    /// Extended ensemble number (bytes 25-32).
+    pub ensemble_number: i64,
+    /// Extended elevation of the receiver group (bytes 33-40).
+    pub receiver_group_elevation: f64,
+    /// Receiver group depth below the surface (bytes 41-48).
+    pub receiver_group_depth: f64,
+    /// Extended surface elevation at the source (bytes 49-56).
+    pub source_surface_elevation: f64,
+    /// Extended source depth below the surface (bytes 57-64).
+    pub source_depth: f64,
+    /// Extended datum elevation at the receiver group (bytes 65-72).
+    pub receiver_datum_elevation: f64,
+    /// Extended datum elevation at the source (bytes 73-80).
+    pub source_datum_elevation: f64,
+    /// Extended water column height at the source (bytes 81-88).
+    pub source_water_depth: f64,
+    /// Extended water column height at the receiver group (bytes 89-96).
+    pub group_water_depth: f64,
+    /// Extended source X coordinate (bytes 97-104).
+    pub source_x: f64,
+    /// Extended source Y coordinate (bytes 105-112).
+    pub source_y: f64,
+    /// Extended group X coordinate (bytes 113-120).
+    pub group_x: f64,
+    /// Extended group Y coordinate (bytes 121-128).
+    pub group_y: f64,
+    /// Extended distance from the source to the receiver group (bytes 129-136).
+    pub offset: f64,
+    /// Extended number of samples in this trace (bytes 137-140).
+    pub trace_sample_count: u32,
+    /// Nanoseconds to add to the second of minute (bytes 141-144).
+    pub nanoseconds: i32,
+    /// Extended sample interval (bytes 145-152).
+    pub sample_interval: f64,
+    /// Cable number or recording device number (bytes 153-156).
+    pub cable_number: i32,
+    /// Number of additional trace headers including this one (bytes 157-158).
+    pub additional_trace_headers: u16,
+    /// Last trace flag (bytes 159-160), 1 for the last trace of an ensemble.
+    pub last_trace_flag: i16,
+    /// Extended ensemble (CDP) X coordinate (bytes 161-168).
+    pub cdp_x: f64,
+    /// Extended ensemble (CDP) Y coordinate (bytes 169-176).
+    pub cdp_y: f64,
+}
+
+impl TraceHeaderExtension1 {
+    /// Fills the extension from a standard trace header, applying the elevation
+    /// and coordinate scalars.
+    pub fn from_trace_header(header: &TraceHeader) -> Self {
+        let elevation = |value: i32| value as f64 * coordinate_scale(header.elevation_scalar);
+        let coordinate = |value: i32| value as f64 * coordinate_scale(header.coord_scalar);
+        TraceHeaderExtension1 {
+            trace_sequence_line: header.trace_sequence_line as u64,
+            trace_sequence_file: header.trace_sequence_file as u64,
+            field_record_number: header.field_record_number as i64,
+            ensemble_number: header.ensemble_number as i64,
+            receiver_group_elevation: elevation(header.receiver_group_elevation),
+            receiver_group_depth: 0.0,
+            source_surface_elevation: elevation(header.source_surface_elevation),
+            source_depth: elevation(header.source_depth),
+            receiver_datum_elevation: elevation(header.receiver_datum_elevation),
+            source_datum_elevation: elevation(header.source_datum_elevation),
+            source_water_depth: elevation(header.source_water_depth),
+            group_water_depth: elevation(header.group_water_depth),
+            source_x: coordinate(header.source_x),
+            source_y: coordinate(header.source_y),
+            group_x: coordinate(header.group_x),
+            group_y: coordinate(header.group_y),
+            offset: header.offset as f64,
+            trace_sample_count: header.trace_sample_count as u32,
+            nanoseconds: 0,
+            sample_interval: header.trace_sample_interval_us as f64,
+            cable_number: 0,
+            additional_trace_headers: 1,
+            last_trace_flag: 0,
+            cdp_x: coordinate(header.cdp_x),
+            cdp_y: coordinate(header.cdp_y),
+        }
+    }
 }
 
 ///SEG-Y file,
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Trace {
     /// The parsed trace header.
     pub header: TraceHeader,
     /// The parsed data samples, stored as `f32`.
     pub data_samples: Vec<f32>,
 }
+
+/// A SEG-Y trace whose samples are kept in the native type of the file's sample format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedTrace {
+    /// The parsed trace header.
+    pub header: TraceHeader,
+    /// The data samples, without any lossy conversion.
+    pub samples: TraceSamples,
+}
+
+impl From<TypedTrace> for Trace {
+    fn from(trace: TypedTrace) -> Self {
+        Trace {
+            header: trace.header,
+            data_samples: trace.samples.into_f32(),
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{
+    ebcdic_syg::{BinaryHeader, TraceHeader},
+    errors::SegyError,
+    mapping::{FieldType, HeaderField, HeaderMapping},
+    reader::{parse_binary_header, parse_trace_header, SegyReader},
+    value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
+    writer::{encode_binary_header, encode_trace_header},
+};
+
+/// A named field of the standard 240-byte trace header, see `TRACE_HEADER_FIELDS`.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceHeaderField {
+    /// Name of the field in `TraceHeader`.
+    pub name: &'static str,
+    /// Location of the field in the SEG-Y rev1 layout.
+    pub field: HeaderField,
+    value: fn(&TraceHeader) -> i32,
+}
+
+impl TraceHeaderField {
+    /// Returns the value of this field in `header`.
+    pub fn value(&self, header: &TraceHeader) -> i32 {
+        (self.value)(header)
+    }
+
+    /// Returns the location of this field, taking the in-line, cross-line and
+    /// CDP X/Y locations from `mapping`.
+    pub fn location(&self, mapping: &HeaderMapping) -> HeaderField {
+        match self.name {
+            "inline_number" => mapping.inline,
+            "crossline_number" => mapping.crossline,
+            "cdp_x" => mapping.cdp_x,
+            "cdp_y" => mapping.cdp_y,
+            _ => self.field,
+        }
+    }
+}
+
+macro_rules! trace_header_fields {
+    ($($name:ident: $byte:literal $field_type:ident,)*) => {
+        &[$(TraceHeaderField {
+            name: stringify!($name),
+            field: HeaderField::new($byte, FieldType::$field_type),
+            value: |header| header.$name as i32,
+        },)*]
+    };
+}
+
+/// Every field of `TraceHeader` with its location, in header order.
+pub const TRACE_HEADER_FIELDS: &[TraceHeaderField] = trace_header_fields! {
+    trace_sequence_line: 1 I32,
+    trace_sequence_file: 5 I32,
+    field_record_number: 9 I32,
+    trace_number: 13 I32,
+    source_point_number: 17 I32,
+    ensemble_number: 21 I32,
+    trace_in_ensemble: 25 I32,
+    trace_identification_code: 29 I16,
+    vertically_summed_traces: 31 I16,
+    horizontally_stacked_traces: 33 I16,
+    data_use: 35 I16,
+    offset: 37 I32,
+    receiver_group_elevation: 41 I32,
+    source_surface_elevation: 45 I32,
+    source_depth: 49 I32,
+    receiver_datum_elevation: 53 I32,
+    source_datum_elevation: 57 I32,
+    source_water_depth: 61 I32,
+    group_water_depth: 65 I32,
+    elevation_scalar: 69 I16,
+    coord_scalar: 71 I16,
+    source_x: 73 I32,
+    source_y: 77 I32,
+    group_x: 81 I32,
+    group_y: 85 I32,
+    coordinate_units: 89 I16,
+    weathering_velocity: 91 I16,
+    subweathering_velocity: 93 I16,
+    source_uphole_time_ms: 95 I16,
+    group_uphole_time_ms: 97 I16,
+    source_static_correction_ms: 99 I16,
+    group_static_correction_ms: 101 I16,
+    total_static_applied_ms: 103 I16,
+    lag_time_a_ms: 105 I16,
+    lag_time_b_ms: 107 I16,
+    delay_recording_time_ms: 109 I16,
+    mute_time_start_ms: 111 I16,
+    mute_time_end_ms: 113 I16,
+    trace_sample_count: 115 U16,
+    trace_sample_interval_us: 117 U16,
+    gain_type: 119 I16,
+    instrument_gain_constant_db: 121 I16,
+    instrument_early_gain_db: 123 I16,
+    correlated: 125 I16,
+    sweep_frequency_start_hz: 127 I16,
+    sweep_frequency_end_hz: 129 I16,
+    sweep_length_ms: 131 I16,
+    sweep_type: 133 I16,
+    sweep_taper_start_ms: 135 I16,
+    sweep_taper_end_ms: 137 I16,
+    taper_type: 139 I16,
+    alias_filter_frequency_hz: 141 I16,
+    alias_filter_slope: 143 I16,
+    notch_filter_frequency_hz: 145 I16,
+    notch_filter_slope: 147 I16,
+    low_cut_frequency_hz: 149 I16,
+    high_cut_frequency_hz: 151 I16,
+    low_cut_slope: 153 I16,
+    high_cut_slope: 155 I16,
+    year_data_recorded: 157 U16,
+    day_of_year: 159 U16,
+    hour_of_day: 161 U16,
+    minute_of_hour: 163 U16,
+    second_of_minute: 165 U16,
+    time_basis_code: 167 I16,
+    trace_weighting_factor: 169 I16,
+    geophone_group_roll_switch: 171 I16,
+    geophone_group_first_trace: 173 I16,
+    geophone_group_last_trace: 175 I16,
+    gap_size: 177 I16,
+    over_travel: 179 I16,
+    cdp_x: 181 I32,
+    cdp_y: 185 I32,
+    inline_number: 189 I32,
+    crossline_number: 193 I32,
+    shotpoint_number: 197 I32,
+    shotpoint_scalar: 201 I16,
+    trace_value_measurement_unit: 203 I16,
+    transduction_constant_mantissa: 205 I32,
+    transduction_constant_exponent: 209 I16,
+    transduction_units: 211 I16,
+    device_identifier: 213 I16,
+    time_scalar: 215 I16,
+    source_type_orientation: 217 I16,
+    source_energy_direction_mantissa: 219 I32,
+    source_energy_direction_exponent: 223 I16,
+    source_measurement_mantissa: 225 I32,
+    source_measurement_exponent: 229 I16,
+    source_measurement_unit: 231 I16,
+};
+
+/// Returns the trace header field called `name`, e.g. `"offset"`.
+pub fn trace_header_field(name: &str) -> Option<&'static TraceHeaderField> {
+    TRACE_HEADER_FIELDS.iter().find(|field| field.name == name)
+}
+
+/// Trace header fields that decide where the following traces start.
+const TRACE_LAYOUT_FIELDS: &[&str] = &["trace_sample_count"];
+
+/// Binary header fields that decide where the traces start, how long they are
+/// or how their samples are decoded.
+const BINARY_LAYOUT_FIELDS: &[&str] = &[
+    "samples_per_trace",
+    "sample_format_code",
+    "segy_revision_major",
+    "fixed_length_traces",
+    "extended_textual_headers",
+    "max_additional_trace_headers",
+    "traces_in_file",
+    "first_trace_offset",
+    "trailer_stanza_records",
+];
+
+/// A header field whose value an edit changes, as reported by `SegyEditor`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Name of the field in `TraceHeader` or `BinaryHeader`.
+    pub name: &'static str,
+    /// First byte of the field, counting from 1 as in the SEG-Y standard, so
+    /// binary header fields start at 3201.
+    pub byte: usize,
+    /// The value in the file.
+    pub old: String,
+    /// The value after the edit.
+    pub new: String,
+}
+
+/// Edits the trace and binary headers of an existing SEG-Y file in place.
+///
+/// Only the bytes of the fields an edit changes are rewritten, so fixing a
+/// header field does not mean rewriting the whole file. In dry-run mode nothing
+/// is written and the edits only report what they would change.
+///
+/// Fields that move or reinterpret the trace data, such as the trace sample
+/// count or the binary header's sample format, are not rewritten unless
+/// `set_allow_layout_changes` is set: the samples themselves are left as they are.
+pub struct SegyEditor<F: Read + Write + Seek> {
+    reader: SegyReader<F>,
+    /// Whether edits are only reported instead of written.
+    dry_run: bool,
+    /// Whether edits may write fields that change the trace layout.
+    allow_layout_changes: bool,
+}
+
+impl SegyEditor<File> {
+    /// Opens the file at `path` for reading and writing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        Self::new(OpenOptions::new().read(true).write(true).open(path)?)
+    }
+}
+
+impl<F: Read + Write + Seek> SegyEditor<F> {
+    /// Creates an editor over any `Read + Write + Seek` file, reading its file headers.
+    pub fn new(file: F) -> Result<Self, SegyError> {
+        Ok(SegyEditor {
+            reader: SegyReader::open(file)?,
+            dry_run: false,
+            allow_layout_changes: false,
+        })
+    }
+
+    /// Returns whether edits are only reported instead of written.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Sets whether edits are only reported instead of written.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// Returns whether edits may write fields that change the trace layout.
+    pub fn allow_layout_changes(&self) -> bool {
+        self.allow_layout_changes
+    }
+
+    /// Sets whether edits may write fields that change where the traces are or
+    /// how their samples are decoded, see `SegyEditor`. Use it to repair headers
+    /// that disagree with the trace data, after checking the change in dry-run mode.
+    pub fn set_allow_layout_changes(&mut self, allow_layout_changes: bool) {
+        self.allow_layout_changes = allow_layout_changes;
+    }
+
+    /// Returns the reader used to locate and decode the headers, e.g. to set its
+    /// header mapping or read traces.
+    pub fn reader(&mut self) -> &mut SegyReader<F> {
+        &mut self.reader
+    }
+
+    /// Applies `edit` to the header of the trace at `index` and writes back the
+    /// fields it changed, returning them.
+    ///
+    /// The header is located and decoded like `SegyReader::trace_header`, with the
+    /// reader's header mapping. Bytes of fields the edit leaves alone, including
+    /// ones `TraceHeader` does not decode, are not touched.
+    ///
+    /// Changing the trace sample count (bytes 115-116) is an error unless layout
+    /// changes are allowed or in dry-run mode.
+    pub fn edit_trace_header<E>(
+        &mut self,
+        index: usize,
+        edit: E,
+    ) -> Result<Vec<FieldChange>, SegyError>
+    where
+        E: FnOnce(&mut TraceHeader),
+    {
+        let offset = self.reader.trace_offset(index)?;
+        let endianness = self.reader.endianness();
+        let mapping = *self.reader.header_mapping();
+        let mut buffer = [0u8; TRACE_HEADER_SIZE];
+        let file = self.reader.get_mut();
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut buffer)?;
+
+        let mut old = parse_trace_header(&buffer, endianness, &mapping)?;
+        old.raw = Some(Box::new(buffer));
+        let mut new = old.clone();
+        edit(&mut new);
+
+        let changes: Vec<FieldChange> = TRACE_HEADER_FIELDS
+            .iter()
+            .filter(|field| field.value(&old) != field.value(&new))
+            .map(|field| FieldChange {
+                name: field.name,
+                byte: field.location(&mapping).byte,
+                old: field.value(&old).to_string(),
+                new: field.value(&new).to_string(),
+            })
+            .collect();
+
+        if !self.dry_run && !changes.is_empty() {
+            let changes_layout = self.check_layout_changes(&changes, TRACE_LAYOUT_FIELDS)?;
+            // The header keeps the bytes it was read from, so only changed fields are encoded.
+            let buffer = encode_trace_header(&new, endianness, &mapping)?;
+            let file = self.reader.get_mut();
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&buffer)?;
+            if changes_layout {
+                // The traces after this one have moved.
+                self.reader.forget_trace_positions();
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Applies `edit` to the binary header and writes back the fields it
+    /// changed, returning them.
+    ///
+    /// The reader is refreshed afterwards, so later edits and reads see the new
+    /// sample format, trace length and counts. The byte order cannot be changed,
+    /// and the fields that locate or decode the traces (sample format, samples per
+    /// trace, fixed-length flag, revision and the header and trailer counts) only
+    /// when layout changes are allowed or in dry-run mode.
+    pub fn edit_binary_header<E>(&mut self, edit: E) -> Result<Vec<FieldChange>, SegyError>
+    where
+        E: FnOnce(&mut BinaryHeader),
+    {
+        let offset = EBCDIC_HEADER_SIZE as u64;
+        let endianness = self.reader.endianness();
+        let mut buffer = [0u8; BINARY_HEADER_SIZE];
+        let file = self.reader.get_mut();
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut buffer)?;
+
+        let old = parse_binary_header(&buffer, endianness)?;
+        let mut new = old.clone();
+        edit(&mut new);
+        if new.endianness != old.endianness {
+            return Err(SegyError::ParseError(
+                "The byte order of a file cannot be changed in place".to_string(),
+            ));
+        }
+
+        let changes: Vec<FieldChange> = binary_header_fields(&old)
+            .into_iter()
+            .zip(binary_header_fields(&new))
+            .filter(|(old_field, new_field)| old_field.3 != new_field.3)
+            .map(|((name, byte, _, old), (_, _, _, new))| FieldChange {
+                name,
+                byte,
+                old,
+                new,
+            })
+            .collect();
+
+        if !self.dry_run && !changes.is_empty() {
+            self.check_layout_changes(&changes, BINARY_LAYOUT_FIELDS)?;
+            let buffer = encode_binary_header(&new)?;
+            let file = self.reader.get_mut();
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&buffer)?;
+            file.flush()?;
+            // Re-read the headers so the reader locates the traces with the new values.
+            file.seek(SeekFrom::Start(offset))?;
+            let binary_header = self.reader.read_binary_header()?;
+            self.reader.read_extended_textual_headers(&binary_header)?;
+        }
+        Ok(changes)
+    }
+
+    /// Returns whether `changes` touch one of `layout_fields`, failing if they do
+    /// and layout changes are not allowed.
+    fn check_layout_changes(
+        &self,
+        changes: &[FieldChange],
+        layout_fields: &[&str],
+    ) -> Result<bool, SegyError> {
+        let Some(change) = changes
+            .iter()
+            .find(|change| layout_fields.contains(&change.name))
+        else {
+            return Ok(false);
+        };
+        if !self.allow_layout_changes {
+            return Err(SegyError::ParseError(format!(
+                "Changing {} from {} to {} changes the trace layout, \
+                 see SegyEditor::set_allow_layout_changes",
+                change.name, change.old, change.new
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Flushes and returns the underlying file.
+    pub fn into_inner(self) -> Result<F, SegyError> {
+        let mut file = self.reader.into_inner();
+        file.flush()?;
+        Ok(file)
+    }
+}
+
+macro_rules! binary_header_fields {
+    ($header:expr, $($name:ident: $byte:literal $width:literal,)*) => {
+        [$((stringify!($name), $byte, $width, format!("{:?}", $header.$name)),)*]
+    };
+}
+
+/// Returns the name, first byte, width and formatted value of every field of
+/// the binary header that is stored in the file.
+pub(crate) fn binary_header_fields(
+    header: &BinaryHeader,
+) -> Vec<(&'static str, usize, usize, String)> {
+    binary_header_fields! {
+        header,
+            job_id: 3201 4,
+            line_number: 3205 4,
+            reel_number: 3209 4,
+            data_traces_per_ensemble: 3213 2,
+            auxiliary_traces_per_ensemble: 3215 2,
+            sample_interval_us: 3217 2,
+            original_sample_interval_us: 3219 2,
+            samples_per_trace: 3221 2,
+            original_samples_per_trace: 3223 2,
+            sample_format_code: 3225 2,
+            ensemble_fold: 3227 2,
+            trace_sorting_code: 3229 2,
+            vertical_sum_code: 3231 2,
+            sweep_frequency_start_hz: 3233 2,
+            sweep_frequency_end_hz: 3235 2,
+            sweep_length_ms: 3237 2,
+            sweep_type_code: 3239 2,
+            sweep_channel: 3241 2,
+            sweep_taper_start_ms: 3243 2,
+            sweep_taper_end_ms: 3245 2,
+            taper_type: 3247 2,
+            correlated_data_traces: 3249 2,
+            binary_gain_recovered: 3251 2,
+            amplitude_recovery_method: 3253 2,
+            measurement_system: 3255 2,
+            impulse_signal_polarity: 3257 2,
+            vibratory_polarity_code: 3259 2,
+            extended_data_traces_per_ensemble: 3261 4,
+            extended_auxiliary_traces_per_ensemble: 3265 4,
+            extended_samples_per_trace: 3269 4,
+            extended_sample_interval: 3273 8,
+            extended_original_sample_interval: 3281 8,
+            extended_original_samples_per_trace: 3289 4,
+            extended_ensemble_fold: 3293 4,
+            byte_order_constant: 3297 4,
+            segy_revision_major: 3501 1,
+            segy_revision_minor: 3502 1,
+            fixed_length_traces: 3503 2,
+            extended_textual_headers: 3505 2,
+            max_additional_trace_headers: 3507 4,
+            time_basis_code: 3511 2,
+            traces_in_file: 3513 8,
+            first_trace_offset: 3521 8,
+            trailer_stanza_records: 3529 4,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        testing::{sample_traces, write_file},
+    };
+
+    fn editor() -> SegyEditor<Cursor<Vec<u8>>> {
+        let bytes = write_file(SampleFormat::IeeeFloat, &sample_traces(4, 4));
+        SegyEditor::new(Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn layout_changes_are_rejected_by_default() {
+        let mut editor = editor();
+        let original = editor.reader().get_mut().get_ref().clone();
+
+        let result = editor.edit_trace_header(1, |header| header.trace_sample_count = 3);
+        assert!(matches!(result, Err(SegyError::ParseError(_))));
+        let result = editor.edit_binary_header(|header| {
+            header.sample_format_code = SampleFormat::Int32;
+        });
+        assert!(matches!(result, Err(SegyError::ParseError(_))));
+        assert_eq!(editor.reader().get_mut().get_ref(), &original);
+
+        // A dry run reports the change without writing it.
+        editor.set_dry_run(true);
+        let changes = editor
+            .edit_binary_header(|header| header.samples_per_trace = 3)
+            .unwrap();
+        assert_eq!(changes[0].name, "samples_per_trace");
+        assert_eq!(editor.reader().get_mut().get_ref(), &original);
+
+        // Other fields are still written.
+        editor.set_dry_run(false);
+        editor
+            .edit_trace_header(1, |header| header.offset = 55)
+            .unwrap();
+        assert_eq!(editor.reader().trace_header(1).unwrap().offset, 55);
+    }
+
+    #[test]
+    fn allowed_layout_changes_relocate_the_traces() {
+        let mut editor = editor();
+        assert_eq!(editor.reader().trace_count().unwrap(), 4);
+        assert_eq!(editor.reader().trace_offset(2).unwrap(), 3600 + 2 * 256);
+
+        editor.set_allow_layout_changes(true);
+        // The traces are about to differ in length.
+        editor
+            .edit_binary_header(|header| header.fixed_length_traces = 0)
+            .unwrap();
+        assert_eq!(editor.reader().trace_offset(2).unwrap(), 3600 + 2 * 256);
+        editor
+            .edit_trace_header(0, |header| header.trace_sample_count = 3)
+            .unwrap();
+        let reader = editor.reader();
+        assert_eq!(reader.fixed_trace_size().unwrap(), None);
+        assert_eq!(reader.trace_offset(1).unwrap(), 3600 + 252);
+    }
+}
@@ -5,9 +5,15 @@ pub enum SegyError {
 
     UnsupportedSampleFormat(u16),
 
-    IbmFloatConversionError,
+    /// An IBM float too large for `f32`, with `IbmOverflow::Error`.
+    IbmFloatOverflow(u32),
+
+    /// A NaN or infinite sample that cannot be encoded as an IBM float, with `IbmNonFinite::Error`.
+    IbmFloatNotFinite(f32),
 
     ParseError(String),
+
+    TraceIndexOutOfRange { index: usize, count: usize },
 }
 impl std::fmt::Display for SegyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -16,8 +22,16 @@ impl std::fmt::Display for SegyError {
             SegyError::UnsupportedSampleFormat(code) => {
                 write!(f, "Unsupported sample format code: {}", code)
             }
-            SegyError::IbmFloatConversionError => write!(f, "Error converting IBM float"),
+            SegyError::IbmFloatOverflow(bits) => {
+                write!(f, "IBM float 0x{:08X} is out of range for f32", bits)
+            }
+            SegyError::IbmFloatNotFinite(value) => {
+                write!(f, "{} cannot be encoded as an IBM float", value)
+            }
             SegyError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            SegyError::TraceIndexOutOfRange { index, count } => {
+                write!(f, "Trace index {} out of range for {} traces", index, count)
+            }
         }
     }
 }
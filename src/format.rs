@@ -2,7 +2,7 @@ use crate::errors::SegyError;
 
 ///  SEG-Y sample formats.
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SampleFormat {
     /// 1 = IBM float (32-bit)
     IbmFloat,
@@ -10,10 +10,28 @@ pub enum SampleFormat {
     Int32,
     /// 3 = 16-bit integer
     Int16,
+    /// 4 = 32-bit fixed point with gain (obsolete)
+    FixedPointWithGain,
     /// 5 = IEEE float (32-bit)
     IeeeFloat,
+    /// 6 = IEEE float (64-bit)
+    IeeeDouble,
+    /// 7 = 24-bit integer
+    Int24,
     /// 8 = 8-bit integer
     Int8,
+    /// 9 = 64-bit integer
+    Int64,
+    /// 10 = 32-bit unsigned integer
+    UInt32,
+    /// 11 = 16-bit unsigned integer
+    UInt16,
+    /// 12 = 64-bit unsigned integer
+    UInt64,
+    /// 15 = 24-bit unsigned integer
+    UInt24,
+    /// 16 = 8-bit unsigned integer
+    UInt8,
 }
 impl SampleFormat {
   
@@ -22,20 +40,103 @@ impl SampleFormat {
             1 => Ok(SampleFormat::IbmFloat),
             2 => Ok(SampleFormat::Int32),
             3 => Ok(SampleFormat::Int16),
+            4 => Ok(SampleFormat::FixedPointWithGain),
             5 => Ok(SampleFormat::IeeeFloat),
+            6 => Ok(SampleFormat::IeeeDouble),
+            7 => Ok(SampleFormat::Int24),
             8 => Ok(SampleFormat::Int8),
+            9 => Ok(SampleFormat::Int64),
+            10 => Ok(SampleFormat::UInt32),
+            11 => Ok(SampleFormat::UInt16),
+            12 => Ok(SampleFormat::UInt64),
+            15 => Ok(SampleFormat::UInt24),
+            16 => Ok(SampleFormat::UInt8),
             other => Err(SegyError::UnsupportedSampleFormat(other)),
         }
     }
 
+    /// Returns the data sample format code stored in bytes 3225-3226 of the binary header.
+    pub fn code(&self) -> u16 {
+        match self {
+            SampleFormat::IbmFloat => 1,
+            SampleFormat::Int32 => 2,
+            SampleFormat::Int16 => 3,
+            SampleFormat::FixedPointWithGain => 4,
+            SampleFormat::IeeeFloat => 5,
+            SampleFormat::IeeeDouble => 6,
+            SampleFormat::Int24 => 7,
+            SampleFormat::Int8 => 8,
+            SampleFormat::Int64 => 9,
+            SampleFormat::UInt32 => 10,
+            SampleFormat::UInt16 => 11,
+            SampleFormat::UInt64 => 12,
+            SampleFormat::UInt24 => 15,
+            SampleFormat::UInt8 => 16,
+        }
+    }
+
     /// Returns the size in bytes of each sample for the given format.
     pub fn sample_size(&self) -> usize {
         match self {
             SampleFormat::IbmFloat => 4,
             SampleFormat::Int32 => 4,
             SampleFormat::Int16 => 2,
+            SampleFormat::FixedPointWithGain => 4,
             SampleFormat::IeeeFloat => 4,
+            SampleFormat::IeeeDouble => 8,
+            SampleFormat::Int24 => 3,
             SampleFormat::Int8 => 1,
+            SampleFormat::Int64 => 8,
+            SampleFormat::UInt32 => 4,
+            SampleFormat::UInt16 => 2,
+            SampleFormat::UInt64 => 8,
+            SampleFormat::UInt24 => 3,
+            SampleFormat::UInt8 => 1,
+        }
+    }
+
+    /// Returns the smallest and largest values of an integer format, or `None`
+    /// for the floating and fixed point formats.
+    pub fn integer_range(&self) -> Option<(i128, i128)> {
+        match self {
+            SampleFormat::Int8 => Some((i8::MIN as i128, i8::MAX as i128)),
+            SampleFormat::Int16 => Some((i16::MIN as i128, i16::MAX as i128)),
+            SampleFormat::Int24 => Some((-(1 << 23), (1 << 23) - 1)),
+            SampleFormat::Int32 => Some((i32::MIN as i128, i32::MAX as i128)),
+            SampleFormat::Int64 => Some((i64::MIN as i128, i64::MAX as i128)),
+            SampleFormat::UInt8 => Some((0, u8::MAX as i128)),
+            SampleFormat::UInt16 => Some((0, u16::MAX as i128)),
+            SampleFormat::UInt24 => Some((0, (1 << 24) - 1)),
+            SampleFormat::UInt32 => Some((0, u32::MAX as i128)),
+            SampleFormat::UInt64 => Some((0, u64::MAX as i128)),
+            SampleFormat::IbmFloat
+            | SampleFormat::FixedPointWithGain
+            | SampleFormat::IeeeFloat
+            | SampleFormat::IeeeDouble => None,
+        }
+    }
+}
+
+/// Byte order of the binary values (headers and samples) in a SEG-Y file.
+///
+/// The standard mandates big-endian, but SEG-Y rev2 allows little-endian files
+/// identified by the byte order constant in bytes 3297-3300.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    /// Most significant byte first, the SEG-Y default.
+    #[default]
+    Big,
+    /// Least significant byte first, as written by much PC software.
+    Little,
+}
+
+impl Endianness {
+    /// Returns the byte order of the target platform.
+    pub fn native() -> Self {
+        if cfg!(target_endian = "little") {
+            Endianness::Little
+        } else {
+            Endianness::Big
         }
     }
 }
@@ -0,0 +1,486 @@
+use std::collections::HashSet;
+
+use crate::{ebcdic_syg::TraceHeader, errors::SegyError, scan::HeaderTable};
+
+/// Marks a grid position without a trace.
+const NO_TRACE: usize = usize::MAX;
+/// Largest number of grid positions `SurveyGeometry` accepts. Line numbers read
+/// from the wrong header bytes easily span a grid far larger than any survey.
+const MAX_GRID_CELLS: usize = 1 << 27;
+
+/// The line numbers along one axis of a 3D survey.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    /// Smallest line number.
+    pub first: i32,
+    /// Largest line number.
+    pub last: i32,
+    /// Increment between consecutive lines. It can exceed `i32::MAX` when the
+    /// range holds only `i32::MIN` and `i32::MAX`.
+    pub step: u32,
+}
+
+impl LineRange {
+    /// Returns the number of lines in the range, zero if `last` is below `first`.
+    ///
+    /// The arithmetic is done in `i64`, so any pair of `i32` line numbers works.
+    pub fn len(&self) -> usize {
+        if self.last < self.first {
+            return 0;
+        }
+        ((self.last as i64 - self.first as i64) / self.step.max(1) as i64) as usize + 1
+    }
+
+    /// Returns true if the range holds no line.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position of `line` in the range, if it is one of its lines.
+    pub fn position(&self, line: i32) -> Option<usize> {
+        if line < self.first || line > self.last {
+            return None;
+        }
+        let offset = line as i64 - self.first as i64;
+        let step = self.step.max(1) as i64;
+        (offset % step == 0).then_some((offset / step) as usize)
+    }
+
+    /// Returns the line number at `position`, which must be below `len`.
+    pub fn line(&self, position: usize) -> i32 {
+        (self.first as i64 + position as i64 * self.step.max(1) as i64) as i32
+    }
+
+    fn from_values(values: &[i32]) -> Self {
+        let first = *values.iter().min().unwrap();
+        let last = *values.iter().max().unwrap();
+        let step = values
+            .iter()
+            .fold(0, |step, &value| {
+                gcd(step, (value as i64 - first as i64) as u64)
+            })
+            .max(1);
+        // Every difference fits in a u32, and so does their divisor.
+        LineRange {
+            first,
+            last,
+            step: step as u32,
+        }
+    }
+}
+
+/// The order in which traces are stored in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Traces of each in-line are stored together.
+    Inline,
+    /// Traces of each cross-line are stored together.
+    Crossline,
+    /// Neither in-lines nor cross-lines are stored contiguously.
+    Unsorted,
+}
+
+/// Affine transform from (in-line, cross-line) to (CDP X, CDP Y) coordinates.
+///
+/// `x = x0 + x_inline * inline + x_crossline * crossline`, and likewise for `y`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AffineTransform {
+    /// X coordinate of in-line 0, cross-line 0.
+    pub x0: f64,
+    /// Change in X per in-line number.
+    pub x_inline: f64,
+    /// Change in X per cross-line number.
+    pub x_crossline: f64,
+    /// Y coordinate of in-line 0, cross-line 0.
+    pub y0: f64,
+    /// Change in Y per in-line number.
+    pub y_inline: f64,
+    /// Change in Y per cross-line number.
+    pub y_crossline: f64,
+}
+
+impl AffineTransform {
+    /// Returns the (X, Y) coordinates of a grid position.
+    pub fn apply(&self, inline: f64, crossline: f64) -> (f64, f64) {
+        (
+            self.x0 + self.x_inline * inline + self.x_crossline * crossline,
+            self.y0 + self.y_inline * inline + self.y_crossline * crossline,
+        )
+    }
+}
+
+/// The geometry of a post-stack 3D survey, inferred from its trace headers.
+#[derive(Debug, Clone)]
+pub struct SurveyGeometry {
+    /// In-line numbers covered by the survey.
+    pub inlines: LineRange,
+    /// Cross-line numbers covered by the survey.
+    pub crosslines: LineRange,
+    /// Order of the traces in the file.
+    pub sorting: SortOrder,
+    /// (in-line, cross-line) positions of the grid without a trace.
+    pub missing: Vec<(i32, i32)>,
+    /// Number of traces at a grid position already taken by an earlier trace.
+    pub duplicate_traces: usize,
+    /// Least-squares fit of the CDP coordinates, after applying the coordinate
+    /// scalar; `None` if the traces do not span a 2D grid.
+    pub transform: Option<AffineTransform>,
+    /// Trace index of every grid position, in-line major.
+    trace_indices: Vec<usize>,
+}
+
+impl SurveyGeometry {
+    /// Infers the geometry from trace headers given in file order.
+    pub fn from_headers<'a, I>(headers: I) -> Result<Self, SegyError>
+    where
+        I: IntoIterator<Item = &'a TraceHeader>,
+    {
+        let mut table = HeaderTable::default();
+        for header in headers {
+            table.push(0, header, header.trace_sample_count);
+        }
+        Self::from_table(&table)
+    }
+
+    /// Infers the geometry from a header table, see `SegyReader::scan_headers`.
+    ///
+    /// Fails if the in-line and cross-line numbers span more than 2^27 grid
+    /// positions, which usually means they were read from the wrong header bytes.
+    pub fn from_table(table: &HeaderTable) -> Result<Self, SegyError> {
+        if table.is_empty() {
+            return Err(SegyError::ParseError(
+                "Cannot infer the geometry of a file without traces".to_string(),
+            ));
+        }
+        let inlines = LineRange::from_values(&table.inline_number);
+        let crosslines = LineRange::from_values(&table.crossline_number);
+
+        let cells = inlines
+            .len()
+            .checked_mul(crosslines.len())
+            .filter(|&cells| cells <= MAX_GRID_CELLS)
+            .ok_or_else(|| {
+                SegyError::ParseError(format!(
+                    "A grid of {} in-lines by {} cross-lines is too large, \
+                     check the header mapping",
+                    inlines.len(),
+                    crosslines.len()
+                ))
+            })?;
+        let mut trace_indices = vec![NO_TRACE; cells];
+        let mut duplicate_traces = 0;
+        for (index, (&inline, &crossline)) in table
+            .inline_number
+            .iter()
+            .zip(&table.crossline_number)
+            .enumerate()
+        {
+            let cell = inlines.position(inline).unwrap() * crosslines.len()
+                + crosslines.position(crossline).unwrap();
+            if trace_indices[cell] == NO_TRACE {
+                trace_indices[cell] = index;
+            } else {
+                duplicate_traces += 1;
+            }
+        }
+
+        let missing = trace_indices
+            .iter()
+            .enumerate()
+            .filter(|(_, &index)| index == NO_TRACE)
+            .map(|(cell, _)| {
+                (
+                    inlines.line(cell / crosslines.len()),
+                    crosslines.line(cell % crosslines.len()),
+                )
+            })
+            .collect();
+
+        let sorting = if is_grouped(&table.inline_number) {
+            SortOrder::Inline
+        } else if is_grouped(&table.crossline_number) {
+            SortOrder::Crossline
+        } else {
+            SortOrder::Unsorted
+        };
+
+        Ok(SurveyGeometry {
+            inlines,
+            crosslines,
+            sorting,
+            missing,
+            duplicate_traces,
+            transform: fit_transform(table),
+            trace_indices,
+        })
+    }
+
+    /// Returns the index of the trace at (in-line, cross-line), if there is one.
+    pub fn trace_index(&self, inline: i32, crossline: i32) -> Option<usize> {
+        let cell = self.inlines.position(inline)? * self.crosslines.len()
+            + self.crosslines.position(crossline)?;
+        Some(self.trace_indices[cell]).filter(|&index| index != NO_TRACE)
+    }
+}
+
+/// Returns true if equal values only appear in contiguous runs.
+fn is_grouped(values: &[i32]) -> bool {
+    let mut seen = HashSet::new();
+    let mut previous = None;
+    for &value in values {
+        if previous != Some(value) && !seen.insert(value) {
+            return false;
+        }
+        previous = Some(value);
+    }
+    true
+}
+
+/// Fits CDP X/Y as affine functions of in-line and cross-line by least squares.
+fn fit_transform(table: &HeaderTable) -> Option<AffineTransform> {
+    // Normal equations A^T A p = A^T b with rows (1, inline, crossline).
+    let mut ata = [[0f64; 3]; 3];
+    let mut atx = [0f64; 3];
+    let mut aty = [0f64; 3];
+    for i in 0..table.len() {
+        let row = [
+            1.0,
+            table.inline_number[i] as f64,
+            table.crossline_number[i] as f64,
+        ];
+        let scale = coordinate_scale(table.coord_scalar[i]);
+        let x = table.cdp_x[i] as f64 * scale;
+        let y = table.cdp_y[i] as f64 * scale;
+        for r in 0..3 {
+            for c in 0..3 {
+                ata[r][c] += row[r] * row[c];
+            }
+            atx[r] += row[r] * x;
+            aty[r] += row[r] * y;
+        }
+    }
+    let [x0, x_inline, x_crossline] = solve3(ata, atx)?;
+    let [y0, y_inline, y_crossline] = solve3(ata, aty)?;
+    Some(AffineTransform {
+        x0,
+        x_inline,
+        x_crossline,
+        y0,
+        y_inline,
+        y_crossline,
+    })
+}
+
+/// Returns the factor a coordinate scalar stands for: positive values multiply,
+/// negative values divide, and zero means no scaling.
+pub fn coordinate_scale(scalar: i16) -> f64 {
+    match scalar {
+        0 => 1.0,
+        s if s > 0 => s as f64,
+        s => 1.0 / -(s as f64),
+    }
+}
+
+/// Solves a 3x3 linear system by Gaussian elimination with partial pivoting.
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..3 {
+            let factor = a[row][col] / pivot_row[col];
+            for (value, pivot_value) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut p = [0f64; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| a[row][k] * p[k]).sum();
+        p[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(p)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(lines: &[(i32, i32)]) -> Vec<TraceHeader> {
+        lines
+            .iter()
+            .map(|&(inline_number, crossline_number)| TraceHeader {
+                inline_number,
+                crossline_number,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Headers of an in-line sorted grid of in-lines 100, 102 and 104 by
+    /// cross-lines 10 to 12, without (102, 11) and with (104, 12) twice. CDP X/Y
+    /// are given in centimetres.
+    fn grid_headers() -> Vec<TraceHeader> {
+        let mut lines = Vec::new();
+        for inline in [100, 102, 104] {
+            for crossline in 10..=12 {
+                if (inline, crossline) != (102, 11) {
+                    lines.push((inline, crossline));
+                }
+            }
+        }
+        lines.push((104, 12));
+        let mut headers = headers(&lines);
+        for header in &mut headers {
+            let (x, y) = grid_coordinates(header.inline_number, header.crossline_number);
+            header.cdp_x = (x * 100.0).round() as i32;
+            header.cdp_y = (y * 100.0).round() as i32;
+            header.coord_scalar = -100;
+        }
+        headers
+    }
+
+    fn grid_coordinates(inline: i32, crossline: i32) -> (f64, f64) {
+        (
+            1000.25 + 20.0 * inline as f64 + 5.5 * crossline as f64,
+            2000.0 + 3.0 * inline as f64 - 10.0 * crossline as f64,
+        )
+    }
+
+    #[test]
+    fn grids_with_holes_and_duplicates_are_described() {
+        let headers = grid_headers();
+        let geometry = SurveyGeometry::from_headers(&headers).unwrap();
+        assert_eq!(
+            geometry.inlines,
+            LineRange {
+                first: 100,
+                last: 104,
+                step: 2
+            }
+        );
+        assert_eq!(
+            geometry.crosslines,
+            LineRange {
+                first: 10,
+                last: 12,
+                step: 1
+            }
+        );
+        assert_eq!(geometry.sorting, SortOrder::Inline);
+        assert_eq!(geometry.missing, [(102, 11)]);
+        assert_eq!(geometry.duplicate_traces, 1);
+        assert_eq!(geometry.trace_index(102, 11), None);
+        assert_eq!(geometry.trace_index(102, 12), Some(4));
+        // The first trace at a position wins.
+        assert_eq!(geometry.trace_index(104, 12), Some(7));
+
+        let transform = geometry.transform.unwrap();
+        for (actual, expected) in [
+            (transform.x0, 1000.25),
+            (transform.x_inline, 20.0),
+            (transform.x_crossline, 5.5),
+            (transform.y0, 2000.0),
+            (transform.y_inline, 3.0),
+            (transform.y_crossline, -10.0),
+        ] {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "{} != {}",
+                actual,
+                expected
+            );
+        }
+        let (x, y) = transform.apply(102.0, 11.0);
+        let (expected_x, expected_y) = grid_coordinates(102, 11);
+        assert!((x - expected_x).abs() < 1e-6 && (y - expected_y).abs() < 1e-6);
+
+        // Stored cross-line by cross-line, the same traces are crossline sorted.
+        let mut by_crossline = headers.clone();
+        by_crossline.sort_by_key(|header| header.crossline_number);
+        let geometry = SurveyGeometry::from_headers(&by_crossline).unwrap();
+        assert_eq!(geometry.sorting, SortOrder::Crossline);
+        let mut unsorted = headers;
+        unsorted.swap(0, 4);
+        let geometry = SurveyGeometry::from_headers(&unsorted).unwrap();
+        assert_eq!(geometry.sorting, SortOrder::Unsorted);
+    }
+
+    #[test]
+    fn traces_along_one_line_have_no_transform() {
+        let geometry = SurveyGeometry::from_headers(&headers(&[(5, 1), (5, 2), (5, 3)])).unwrap();
+        assert_eq!(geometry.transform, None);
+        assert_eq!(
+            solve3(
+                [[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 0.0, 1.0]],
+                [1.0; 3]
+            ),
+            None
+        );
+        assert_eq!(
+            solve3(
+                [[0.0, 2.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 4.0]],
+                [4.0, 3.0, 2.0]
+            ),
+            Some([3.0, 2.0, 0.5])
+        );
+    }
+
+    #[test]
+    fn grouped_runs_and_coordinate_scalars() {
+        assert!(is_grouped(&[3, 3, 1, 1, 2]));
+        assert!(!is_grouped(&[3, 1, 3]));
+        assert_eq!(coordinate_scale(0), 1.0);
+        assert_eq!(coordinate_scale(10), 10.0);
+        assert_eq!(coordinate_scale(-100), 0.01);
+    }
+
+    #[test]
+    fn line_ranges_span_the_whole_i32_range() {
+        let geometry =
+            SurveyGeometry::from_headers(&headers(&[(i32::MIN, 5), (i32::MAX, 5)])).unwrap();
+        let inlines = geometry.inlines;
+        assert_eq!(inlines.step, u32::MAX);
+        assert_eq!(inlines.len(), 2);
+        assert_eq!(inlines.position(i32::MAX), Some(1));
+        assert_eq!(inlines.position(0), None);
+        assert_eq!(inlines.line(1), i32::MAX);
+        assert_eq!(geometry.trace_index(i32::MAX, 5), Some(1));
+        assert!(geometry.missing.is_empty());
+    }
+
+    #[test]
+    fn line_ranges_know_when_they_are_empty() {
+        let range = LineRange {
+            first: -4,
+            last: 8,
+            step: 4,
+        };
+        assert_eq!(range.len(), 4);
+        assert!(!range.is_empty());
+        assert_eq!(range.position(4), Some(2));
+        let empty = LineRange { last: -5, ..range };
+        assert_eq!(empty.len(), 0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn oversized_grids_are_errors() {
+        let result =
+            SurveyGeometry::from_headers(&headers(&[(0, 0), (1, 1), (1_000_000, 1_000_000)]));
+        assert!(matches!(result, Err(SegyError::ParseError(_))));
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::SegyError,
+    mapping::HeaderMapping,
+    open_segy_file,
+    scan::HeaderTable,
+    sidecar::{sidecar_path, FileStamp, SidecarReader, SidecarWriter},
+};
+
+/// Magic bytes at the start of a saved trace index.
+const INDEX_MAGIC: &[u8; 8] = b"SGYIDX02";
+
+/// The trace header values a `TraceIndex` is keyed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKey {
+    /// Ensemble (CDP) number (bytes 21-24).
+    EnsembleNumber,
+    /// Original field record number (bytes 9-12) and trace number within it (bytes 13-16).
+    FieldRecordTrace,
+    /// In-line and cross-line numbers, as located by the header mapping.
+    InlineCrossline,
+}
+
+impl IndexKey {
+    /// Returns the number of header values in the key.
+    pub fn arity(&self) -> usize {
+        match self {
+            IndexKey::EnsembleNumber => 1,
+            IndexKey::FieldRecordTrace | IndexKey::InlineCrossline => 2,
+        }
+    }
+
+    /// Returns a short name used in sidecar file names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexKey::EnsembleNumber => "ensemble",
+            IndexKey::FieldRecordTrace => "record-trace",
+            IndexKey::InlineCrossline => "inline-crossline",
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            IndexKey::EnsembleNumber => 1,
+            IndexKey::FieldRecordTrace => 2,
+            IndexKey::InlineCrossline => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Self, SegyError> {
+        match code {
+            1 => Ok(IndexKey::EnsembleNumber),
+            2 => Ok(IndexKey::FieldRecordTrace),
+            3 => Ok(IndexKey::InlineCrossline),
+            other => Err(SegyError::ParseError(format!(
+                "Unknown trace index key code: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Returns the key values of row `row` of a header table.
+    fn values(&self, table: &HeaderTable, row: usize) -> Vec<i32> {
+        match self {
+            IndexKey::EnsembleNumber => vec![table.ensemble_number[row]],
+            IndexKey::FieldRecordTrace => {
+                vec![table.field_record_number[row], table.trace_number[row]]
+            }
+            IndexKey::InlineCrossline => {
+                vec![table.inline_number[row], table.crossline_number[row]]
+            }
+        }
+    }
+}
+
+/// Maps trace header key values to the byte offsets of the matching traces,
+/// e.g. every trace of CDP 1234.
+///
+/// The index can be saved next to the SEG-Y file and reloaded without
+/// rescanning; it records the file's size and modification time and the header
+/// mapping it was built with, so that a stale index is detected and rebuilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceIndex {
+    /// The header values the index is keyed by.
+    pub key: IndexKey,
+    /// Locations the in-line and cross-line numbers were read from.
+    pub mapping: HeaderMapping,
+    /// Stamp of the SEG-Y file the index was built from.
+    pub stamp: FileStamp,
+    entries: BTreeMap<Vec<i32>, Vec<u64>>,
+}
+
+impl TraceIndex {
+    /// Builds an index from a header table scanned with `mapping`, see
+    /// `SegyReader::scan_headers`.
+    pub fn from_table(
+        table: &HeaderTable,
+        key: IndexKey,
+        mapping: HeaderMapping,
+        stamp: FileStamp,
+    ) -> Self {
+        let mut entries: BTreeMap<Vec<i32>, Vec<u64>> = BTreeMap::new();
+        for row in 0..table.len() {
+            entries
+                .entry(key.values(table, row))
+                .or_default()
+                .push(table.trace_offsets[row]);
+        }
+        TraceIndex {
+            key,
+            mapping,
+            stamp,
+            entries,
+        }
+    }
+
+    /// Scans the trace headers of the SEG-Y file at `path` and indexes them,
+    /// reading the in-line and cross-line numbers from the locations in `mapping`.
+    pub fn build<P: AsRef<Path>>(
+        path: P,
+        key: IndexKey,
+        mapping: &HeaderMapping,
+    ) -> Result<Self, SegyError> {
+        let stamp = FileStamp::of(&path)?;
+        let mut reader = open_segy_file(&path)?;
+        reader.set_header_mapping(*mapping);
+        let table = reader.scan_headers()?;
+        Ok(Self::from_table(&table, key, *mapping, stamp))
+    }
+
+    /// Loads the sidecar index of the SEG-Y file at `path`, or builds it and saves
+    /// it when it is missing, unreadable, stale or built with another mapping.
+    pub fn load_or_build<P: AsRef<Path>>(
+        path: P,
+        key: IndexKey,
+        mapping: &HeaderMapping,
+    ) -> Result<Self, SegyError> {
+        let sidecar = Self::sidecar_path(&path, key);
+        if let Ok(index) = Self::load(&sidecar) {
+            if index.key == key && index.mapping == *mapping && !index.is_stale(&path)? {
+                return Ok(index);
+            }
+        }
+        let index = Self::build(&path, key, mapping)?;
+        index.save(&sidecar)?;
+        Ok(index)
+    }
+
+    /// Returns the conventional sidecar location for an index of the SEG-Y file
+    /// at `path`, e.g. `survey.sgy.ensemble.idx`.
+    pub fn sidecar_path<P: AsRef<Path>>(path: P, key: IndexKey) -> PathBuf {
+        sidecar_path(path, &format!(".{}.idx", key.name()))
+    }
+
+    /// Returns true if the SEG-Y file at `path` changed since the index was built.
+    pub fn is_stale<P: AsRef<Path>>(&self, path: P) -> Result<bool, SegyError> {
+        Ok(FileStamp::of(path)? != self.stamp)
+    }
+
+    /// Returns the offsets of the traces whose key values equal `values`, in file order.
+    ///
+    /// `values` holds one value per header field of the key, e.g. `&[1234]` for
+    /// `IndexKey::EnsembleNumber`; use `SegyReader::read_trace_at` to read them.
+    pub fn offsets(&self, values: &[i32]) -> &[u64] {
+        self.entries.get(values).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the distinct key values in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &[i32]> {
+        self.entries.keys().map(Vec::as_slice)
+    }
+
+    /// Returns the number of distinct key values.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the index holds no traces.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serializes the index in a compact little-endian binary format.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), SegyError> {
+        let mut writer = SidecarWriter::new(writer, INDEX_MAGIC)?;
+        writer.u8(self.key.code())?;
+        writer.mapping(&self.mapping)?;
+        writer.stamp(&self.stamp)?;
+        writer.u64(self.entries.len() as u64)?;
+        for (values, offsets) in &self.entries {
+            for &value in values {
+                writer.i32(value)?;
+            }
+            writer.u64(offsets.len() as u64)?;
+            for &offset in offsets {
+                writer.u64(offset)?;
+            }
+        }
+        writer.finish()
+    }
+
+    /// Deserializes an index written by `write_to`.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, SegyError> {
+        let mut reader = SidecarReader::new(reader, INDEX_MAGIC, "trace index")?;
+        let key = IndexKey::from_code(reader.u8()?)?;
+        let mapping = reader.mapping()?;
+        let stamp = reader.stamp()?;
+
+        let mut entries = BTreeMap::new();
+        for _ in 0..reader.u64()? {
+            let values = (0..key.arity())
+                .map(|_| reader.i32())
+                .collect::<Result<Vec<_>, _>>()?;
+            let offsets = (0..reader.u64()?)
+                .map(|_| reader.u64())
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.insert(values, offsets);
+        }
+        Ok(TraceIndex {
+            key,
+            mapping,
+            stamp,
+            entries,
+        })
+    }
+
+    /// Saves the index to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SegyError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Loads an index saved with `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        mapping::{FieldType, HeaderField},
+        testing::{sample_traces, temp_file, write_file},
+    };
+
+    #[test]
+    fn saved_index_round_trips() {
+        let path = temp_file(
+            "index.sgy",
+            &write_file(SampleFormat::IeeeFloat, &sample_traces(6, 4)),
+        );
+        let index =
+            TraceIndex::build(&path, IndexKey::InlineCrossline, &HeaderMapping::rev1()).unwrap();
+        assert_eq!(index.len(), 6);
+        assert_eq!(index.offsets(&[101, 202]), &[3600 + 5 * (240 + 16)]);
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        assert_eq!(TraceIndex::read_from(&bytes[..]).unwrap(), index);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn index_built_with_another_mapping_is_rebuilt() {
+        let path = temp_file(
+            "mapping.sgy",
+            &write_file(SampleFormat::IeeeFloat, &sample_traces(6, 4)),
+        );
+        let key = IndexKey::InlineCrossline;
+        let rev1 = TraceIndex::load_or_build(&path, key, &HeaderMapping::rev1()).unwrap();
+        assert_eq!(rev1.len(), 6);
+
+        // Reading both numbers from the trace sequence number collapses each
+        // trace onto the diagonal, which a stale sidecar would not show.
+        let sequence = HeaderField::new(1, FieldType::I32);
+        let mapping = HeaderMapping {
+            inline: sequence,
+            crossline: sequence,
+            ..HeaderMapping::rev1()
+        };
+        let rebuilt = TraceIndex::load_or_build(&path, key, &mapping).unwrap();
+        assert_eq!(rebuilt.mapping, mapping);
+        assert_eq!(rebuilt.offsets(&[3, 3]), &[3600 + 2 * (240 + 16)]);
+        assert_eq!(
+            TraceIndex::load(TraceIndex::sidecar_path(&path, key)).unwrap(),
+            rebuilt
+        );
+
+        std::fs::remove_file(TraceIndex::sidecar_path(&path, key)).unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,26 +1,50 @@
 use std::{
     fs::File,
-    io::{Seek, SeekFrom},
+    io::{BufReader, BufWriter, Seek, SeekFrom},
     path::Path,
 };
 
+use convert::{convert_sample_format, ConversionReport};
+use ebcdic_syg::EbcdicHeader;
 use errors::SegyError;
+use format::SampleFormat;
 use reader::SegyReader;
 use sgy::SegyFile;
+use stanza::parse_stanzas;
+use writer::SegyWriter;
 
+#[cfg(feature = "tokio")]
+pub mod async_reader;
+pub mod convert;
 pub mod ebcdic_syg;
+pub mod edit;
 pub mod errors;
 pub mod format;
+pub mod geometry;
+pub mod index;
+pub mod mapping;
+pub mod mmap;
+pub mod offsets;
 pub mod reader;
+pub mod samples;
+pub mod scan;
 pub mod sgy;
+pub mod sidecar;
+pub mod stanza;
+#[cfg(test)]
+mod testing;
 pub mod utils;
 pub mod value;
+pub mod volume;
+pub mod writer;
 
 pub fn read_segy_from_file<P: AsRef<Path>>(path: P) -> Result<SegyFile, SegyError> {
     let mut file = File::open(path)?;
     let mut cloned_file = file.try_clone()?; 
 
     let mut reader = SegyReader::new(&mut cloned_file);
+    // Keep the header bytes so `write_segy_to_file` can write them back unchanged.
+    reader.set_keep_raw_trace_headers(true);
 
     // 1. Read EBCDIC header
     file.seek(SeekFrom::Start(0))?; 
@@ -29,12 +53,70 @@ pub fn read_segy_from_file<P: AsRef<Path>>(path: P) -> Result<SegyFile, SegyErro
     // 2. Read binary header
     let binary_header = reader.read_binary_header()?;
 
-    // 3. Read all traces
+    // 3. Read extended textual headers, if any
+    let extended_headers = reader.read_extended_textual_headers(&binary_header)?;
+    let stanzas = parse_stanzas(&extended_headers);
+
+    // 4. Read all traces
     let traces = reader.read_all_traces(&binary_header)?;
 
+    // 5. Read the rev2 data trailer, if any
+    let trailer_records = reader.read_trailer_records()?;
+    let trailer_stanzas = parse_stanzas(&trailer_records);
+
     Ok(SegyFile {
         ebcdic_header: ebcdic_header.text,
+        ebcdic_header_raw: ebcdic_header.raw,
         binary_header,
+        extended_textual_headers: extended_headers,
+        stanzas,
         traces,
+        trailer_records,
+        trailer_stanzas,
     })
 }
+
+/// Opens a SEG-Y file for streaming, reading its file headers up front.
+///
+/// Use `SegyReader::traces` or `SegyReader::trace_headers` on the result to walk
+/// the traces one at a time instead of loading the whole file.
+pub fn open_segy_file<P: AsRef<Path>>(path: P) -> Result<SegyReader<BufReader<File>>, SegyError> {
+    SegyReader::open(BufReader::new(File::open(path)?))
+}
+
+/// Converts the SEG-Y file at `input` to the `target` sample format, writing the
+/// result to `output`, see `convert::convert_sample_format`.
+pub fn convert_segy_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    target: SampleFormat,
+) -> Result<ConversionReport, SegyError> {
+    let mut reader = open_segy_file(input)?;
+    convert_sample_format(&mut reader, BufWriter::new(File::create(output)?), target)
+}
+
+/// Writes a `SegyFile` to disk, the inverse of `read_segy_from_file`.
+///
+/// Binary values are written in the byte order of `segy_file.binary_header`, and
+/// its counts are updated to match what was written. Headers read from a file
+/// keep their original bytes wherever their fields or text were not changed;
+/// other textual headers are written in EBCDIC.
+pub fn write_segy_to_file<P: AsRef<Path>>(path: P, segy_file: &SegyFile) -> Result<(), SegyError> {
+    let mut writer = SegyWriter::new(BufWriter::new(File::create(path)?));
+    writer.write_ebcdic_header(&EbcdicHeader {
+        text: segy_file.ebcdic_header.clone(),
+        raw: segy_file.ebcdic_header_raw.clone(),
+    })?;
+    writer.write_binary_header(&segy_file.binary_header)?;
+    for header in &segy_file.extended_textual_headers {
+        writer.write_extended_ebcdic_header(header)?;
+    }
+    for trace in &segy_file.traces {
+        writer.write_trace(trace)?;
+    }
+    for record in &segy_file.trailer_records {
+        writer.write_trailer_ebcdic_record(record)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
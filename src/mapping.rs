@@ -0,0 +1,273 @@
+use crate::{
+    ebcdic_syg::TraceHeader,
+    errors::SegyError,
+    format::Endianness,
+    utils::{read_i16, read_i32, read_u16, read_u32, write_i16, write_i32, write_u16, write_u32},
+};
+
+/// Width and signedness of an integer trace header field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// 2-byte two's complement integer.
+    I16,
+    /// 2-byte unsigned integer.
+    U16,
+    /// 4-byte two's complement integer.
+    I32,
+    /// 4-byte unsigned integer; values above `i32::MAX` wrap around.
+    U32,
+}
+
+impl FieldType {
+    /// Returns the width of the field in bytes.
+    pub fn width(&self) -> usize {
+        match self {
+            FieldType::I16 | FieldType::U16 => 2,
+            FieldType::I32 | FieldType::U32 => 4,
+        }
+    }
+}
+
+/// Location of an integer field within the 240-byte trace header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderField {
+    /// First byte of the field, counting from 1 as in the SEG-Y standard.
+    pub byte: usize,
+    /// Width and signedness of the field.
+    pub field_type: FieldType,
+}
+
+impl HeaderField {
+    /// Creates a field starting at `byte` (counting from 1).
+    pub const fn new(byte: usize, field_type: FieldType) -> Self {
+        HeaderField { byte, field_type }
+    }
+
+    /// Reads the field from a trace header buffer.
+    pub fn read(&self, buffer: &[u8], endianness: Endianness) -> Result<i32, SegyError> {
+        let offset = self.byte.checked_sub(1).ok_or_else(|| {
+            SegyError::ParseError("Header byte locations start at 1".to_string())
+        })?;
+        Ok(match self.field_type {
+            FieldType::I16 => read_i16(buffer, offset, endianness)? as i32,
+            FieldType::U16 => read_u16(buffer, offset, endianness)? as i32,
+            FieldType::I32 => read_i32(buffer, offset, endianness)?,
+            FieldType::U32 => read_u32(buffer, offset, endianness)? as i32,
+        })
+    }
+
+    /// Writes `value` into the field of a trace header buffer, truncating it to
+    /// the field's width.
+    pub fn write(
+        &self,
+        buffer: &mut [u8],
+        value: i32,
+        endianness: Endianness,
+    ) -> Result<(), SegyError> {
+        let offset = self.byte.checked_sub(1).ok_or_else(|| {
+            SegyError::ParseError("Header byte locations start at 1".to_string())
+        })?;
+        match self.field_type {
+            FieldType::I16 => write_i16(buffer, offset, value as i16, endianness),
+            FieldType::U16 => write_u16(buffer, offset, value as u16, endianness),
+            FieldType::I32 => write_i32(buffer, offset, value, endianness),
+            FieldType::U32 => write_u32(buffer, offset, value as u32, endianness),
+        }
+    }
+}
+
+/// Where the reader finds the in-line and cross-line numbers and the ensemble
+/// (CDP) coordinates in the trace header.
+///
+/// Vendors disagree on these locations, so they are configurable; the decoded
+/// values end up in `TraceHeader::inline_number`, `crossline_number`, `cdp_x`
+/// and `cdp_y`. The default follows SEG-Y rev1.
+///
+/// Earlier versions of this crate read CDP X/Y from the source coordinates at
+/// bytes 73/77; use `HeaderMapping::rev1().with_cdp_from_source()` for files
+/// that keep them there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderMapping {
+    /// Location of the in-line number.
+    pub inline: HeaderField,
+    /// Location of the cross-line number.
+    pub crossline: HeaderField,
+    /// Location of the ensemble (CDP) X coordinate.
+    pub cdp_x: HeaderField,
+    /// Location of the ensemble (CDP) Y coordinate.
+    pub cdp_y: HeaderField,
+}
+
+impl Default for HeaderMapping {
+    fn default() -> Self {
+        Self::rev1()
+    }
+}
+
+impl HeaderMapping {
+    /// The SEG-Y rev1 locations: in-line at 189, cross-line at 193 and CDP X/Y at 181/185.
+    pub const fn rev1() -> Self {
+        HeaderMapping {
+            inline: HeaderField::new(189, FieldType::I32),
+            crossline: HeaderField::new(193, FieldType::I32),
+            cdp_x: HeaderField::new(181, FieldType::I32),
+            cdp_y: HeaderField::new(185, FieldType::I32),
+        }
+    }
+
+    /// In-line in the field record number (9) and cross-line in the ensemble number (21).
+    pub const fn field_record_ensemble() -> Self {
+        HeaderMapping {
+            inline: HeaderField::new(9, FieldType::I32),
+            crossline: HeaderField::new(21, FieldType::I32),
+            ..Self::rev1()
+        }
+    }
+
+    /// In-line in the energy source point number (17) and cross-line in the
+    /// trace number within the field record (13).
+    pub const fn source_point_trace() -> Self {
+        HeaderMapping {
+            inline: HeaderField::new(17, FieldType::I32),
+            crossline: HeaderField::new(13, FieldType::I32),
+            ..Self::rev1()
+        }
+    }
+
+    /// Takes the CDP X/Y coordinates from the source coordinates at bytes 73/77,
+    /// as some older files do.
+    pub const fn with_cdp_from_source(self) -> Self {
+        HeaderMapping {
+            cdp_x: HeaderField::new(73, FieldType::I32),
+            cdp_y: HeaderField::new(77, FieldType::I32),
+            ..self
+        }
+    }
+
+    /// Sets the location of a field by name: `inline`, `crossline`, `cdp_x` or `cdp_y`.
+    pub fn set(&mut self, name: &str, field: HeaderField) -> Result<(), SegyError> {
+        match name {
+            "inline" => self.inline = field,
+            "crossline" => self.crossline = field,
+            "cdp_x" => self.cdp_x = field,
+            "cdp_y" => self.cdp_y = field,
+            other => {
+                return Err(SegyError::ParseError(format!(
+                    "Unknown header mapping field: {}",
+                    other
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the mapped fields from a trace header buffer into `header`.
+    pub fn apply(
+        &self,
+        buffer: &[u8],
+        endianness: Endianness,
+        header: &mut TraceHeader,
+    ) -> Result<(), SegyError> {
+        header.inline_number = self.inline.read(buffer, endianness)?;
+        header.crossline_number = self.crossline.read(buffer, endianness)?;
+        header.cdp_x = self.cdp_x.read(buffer, endianness)?;
+        header.cdp_y = self.cdp_y.read(buffer, endianness)?;
+        Ok(())
+    }
+
+    /// Writes the in-line, cross-line and CDP X/Y numbers of `header` to their
+    /// mapped locations in a trace header buffer; the inverse of `apply`.
+    pub fn write(
+        &self,
+        header: &TraceHeader,
+        buffer: &mut [u8],
+        endianness: Endianness,
+    ) -> Result<(), SegyError> {
+        self.inline.write(buffer, header.inline_number, endianness)?;
+        self.crossline.write(buffer, header.crossline_number, endianness)?;
+        self.cdp_x.write(buffer, header.cdp_x, endianness)?;
+        self.cdp_y.write(buffer, header.cdp_y, endianness)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A trace header whose 4-byte fields at 9, 13, 17, 21, 73, 77, 181, 185,
+    /// 189 and 193 hold their own byte location.
+    fn header_bytes() -> Vec<u8> {
+        let mut buffer = vec![0u8; 240];
+        for byte in [9, 13, 17, 21, 73, 77, 181, 185, 189, 193] {
+            buffer[byte - 1..byte + 3].copy_from_slice(&(byte as i32).to_be_bytes());
+        }
+        buffer
+    }
+
+    fn mapped(mapping: HeaderMapping) -> [i32; 4] {
+        let mut header = TraceHeader::default();
+        mapping
+            .apply(&header_bytes(), Endianness::Big, &mut header)
+            .unwrap();
+        [
+            header.inline_number,
+            header.crossline_number,
+            header.cdp_x,
+            header.cdp_y,
+        ]
+    }
+
+    #[test]
+    fn presets_read_their_locations() {
+        assert_eq!(mapped(HeaderMapping::default()), [189, 193, 181, 185]);
+        assert_eq!(mapped(HeaderMapping::rev1()), [189, 193, 181, 185]);
+        assert_eq!(
+            mapped(HeaderMapping::field_record_ensemble()),
+            [9, 21, 181, 185]
+        );
+        assert_eq!(
+            mapped(HeaderMapping::source_point_trace()),
+            [17, 13, 181, 185]
+        );
+        assert_eq!(
+            mapped(HeaderMapping::rev1().with_cdp_from_source()),
+            [189, 193, 73, 77]
+        );
+    }
+
+    #[test]
+    fn fields_are_set_by_name() {
+        let mut mapping = HeaderMapping::rev1();
+        mapping
+            .set("inline", HeaderField::new(9, FieldType::I32))
+            .unwrap();
+        mapping
+            .set("cdp_y", HeaderField::new(77, FieldType::I32))
+            .unwrap();
+        assert_eq!(mapped(mapping), [9, 193, 181, 77]);
+        assert!(mapping
+            .set("offset", HeaderField::new(37, FieldType::I32))
+            .is_err());
+    }
+
+    #[test]
+    fn fields_are_read_and_written_at_their_width() {
+        let mut buffer = vec![0u8; 240];
+        let field = HeaderField::new(181, FieldType::U32);
+        field.write(&mut buffer, -2, Endianness::Little).unwrap();
+        assert_eq!(buffer[180..184], [0xFE, 0xFF, 0xFF, 0xFF]);
+        // 4294967294 does not fit in an i32, so it wraps around.
+        assert_eq!(field.read(&buffer, Endianness::Little).unwrap(), -2);
+
+        let field = HeaderField::new(189, FieldType::U16);
+        field.write(&mut buffer, 65535, Endianness::Big).unwrap();
+        assert_eq!(field.read(&buffer, Endianness::Big).unwrap(), 65535);
+        let field = HeaderField::new(189, FieldType::I16);
+        assert_eq!(field.read(&buffer, Endianness::Big).unwrap(), -1);
+
+        assert!(HeaderField::new(0, FieldType::I32)
+            .read(&buffer, Endianness::Big)
+            .is_err());
+    }
+}
@@ -0,0 +1,632 @@
+use std::{fs::File, path::Path};
+
+use memmap2::Mmap;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    ebcdic_syg::{
+        BinaryHeader, EbcdicHeader, Trace, TraceHeader, TraceHeaderExtension1, TypedTrace,
+    },
+    errors::SegyError,
+    format::{Endianness, SampleFormat},
+    mapping::HeaderMapping,
+    offsets::TraceOffsets,
+    reader::{
+        decode_textual_header, detect_endianness, parse_binary_header, parse_trace_header,
+        parse_trace_header_extension, ExtendedTextualHeaders, TraceLayout,
+    },
+    samples::TraceSamples,
+    scan::HeaderTable,
+    sidecar::FileStamp,
+    utils::{decode_ibm_slice, read_u32, IbmFloatPolicy},
+    value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
+};
+
+/// A memory-mapped SEG-Y reader.
+///
+/// Trace headers and samples are decoded directly from the mapped file, and
+/// raw header and sample bytes are handed out as borrowed slices of the map.
+pub struct MmapSegyReader {
+    mmap: Mmap,
+    ebcdic_header: EbcdicHeader,
+    binary_header: BinaryHeader,
+    extended_textual_headers: Vec<EbcdicHeader>,
+    layout: TraceLayout,
+    endianness: Endianness,
+    data_offset: usize,
+    trace_size: usize,
+    trace_count: usize,
+    trace_offsets: Option<TraceOffsets>,
+    header_mapping: HeaderMapping,
+    /// How IBM float samples outside the `f32` range are converted.
+    ibm_policy: IbmFloatPolicy,
+    /// Whether parsed trace headers keep their bytes in `TraceHeader::raw`.
+    keep_raw_trace_headers: bool,
+}
+
+impl MmapSegyReader {
+    /// Maps the file at `path` and parses its file headers.
+    ///
+    /// If the traces are not all of the same length, the trace headers are
+    /// scanned once to build a trace offset table.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        let stamp = FileStamp::of(&path)?;
+        let mut reader = Self::from_mmap(Self::map(path)?)?;
+        // Stamp a scanned table with the modification time as well, so that it
+        // can be saved and passed to `open_with_trace_offsets` later.
+        if let Some(trace_offsets) = &mut reader.trace_offsets {
+            if trace_offsets.stamp.len == stamp.len {
+                trace_offsets.stamp = stamp;
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Maps the file at `path`, reusing a trace offset table built earlier
+    /// (e.g. with `TraceOffsets::load_or_build`) instead of scanning the file.
+    ///
+    /// The table is rejected if the file changed since it was built, see
+    /// `TraceOffsets::is_stale`.
+    pub fn open_with_trace_offsets<P: AsRef<Path>>(
+        path: P,
+        trace_offsets: TraceOffsets,
+    ) -> Result<Self, SegyError> {
+        if trace_offsets.is_stale(&path)? {
+            return Err(SegyError::ParseError(
+                "Trace offset table is stale: the file changed since it was built".to_string(),
+            ));
+        }
+        let mmap = Self::map(path)?;
+        if trace_offsets.stamp.len != mmap.len() as u64 {
+            return Err(SegyError::ParseError(format!(
+                "Trace offset table was built for a file of {} bytes, not {}",
+                trace_offsets.stamp.len,
+                mmap.len()
+            )));
+        }
+        Self::parse(mmap, Some(trace_offsets))
+    }
+
+    /// Parses the file headers of an existing map.
+    pub fn from_mmap(mmap: Mmap) -> Result<Self, SegyError> {
+        Self::parse(mmap, None)
+    }
+
+    fn map<P: AsRef<Path>>(path: P) -> Result<Mmap, SegyError> {
+        let file = File::open(path)?;
+        // Safety: the map is read-only; as with any mmap, the file must not be
+        // truncated or modified by another process while it is mapped.
+        Ok(unsafe { Mmap::map(&file)? })
+    }
+
+    fn parse(mmap: Mmap, trace_offsets: Option<TraceOffsets>) -> Result<Self, SegyError> {
+        let bytes: &[u8] = &mmap;
+        let binary_start = EBCDIC_HEADER_SIZE;
+        let binary_end = binary_start + BINARY_HEADER_SIZE;
+        if bytes.len() < binary_end {
+            return Err(SegyError::ParseError(
+                "File is too short to hold the SEG-Y file headers".to_string(),
+            ));
+        }
+
+        let ebcdic_header = decode_textual_header(&bytes[..EBCDIC_HEADER_SIZE]);
+        let endianness = detect_endianness(&bytes[binary_start..binary_end]);
+        let binary_header = parse_binary_header(&bytes[binary_start..binary_end], endianness)?;
+
+        let mut extended_textual_headers =
+            ExtendedTextualHeaders::new(binary_header.extended_textual_headers)?;
+        let mut position = binary_end;
+        while !extended_textual_headers.is_complete() {
+            let record = bytes
+                .get(position..position + EBCDIC_HEADER_SIZE)
+                .ok_or_else(|| {
+                    SegyError::ParseError("Truncated extended textual header".to_string())
+                })?;
+            extended_textual_headers.push(record);
+            position += EBCDIC_HEADER_SIZE;
+        }
+        let extended_textual_headers = extended_textual_headers.into_headers();
+
+        let layout = TraceLayout::from(&binary_header);
+        let data_offset = if layout.first_trace_offset > 0 {
+            layout.first_trace_offset as usize
+        } else {
+            position
+        };
+        let trace_size = layout.trace_size(layout.default_samples_per_trace) as usize;
+        let data_len = bytes.len().saturating_sub(data_offset);
+        let trace_count = if layout.traces_in_file > 0 {
+            layout.announced_traces(data_len as u64) as usize
+        } else {
+            data_len / trace_size
+        };
+
+        let mut reader = MmapSegyReader {
+            ebcdic_header,
+            binary_header,
+            extended_textual_headers,
+            layout,
+            endianness,
+            data_offset,
+            trace_size,
+            trace_count,
+            trace_offsets: None,
+            header_mapping: HeaderMapping::default(),
+            ibm_policy: IbmFloatPolicy::default(),
+            keep_raw_trace_headers: false,
+            mmap,
+        };
+        let trace_offsets = match trace_offsets {
+            Some(trace_offsets) => Some(trace_offsets),
+            None => {
+                let is_fixed = reader.binary_header.fixed_length_traces == 1
+                    || (data_len % trace_size == 0
+                        && (trace_count == 0
+                            || reader.samples_in_trace(0)? == layout.default_samples_per_trace));
+                if is_fixed {
+                    None
+                } else {
+                    Some(reader.scan_trace_offsets()?)
+                }
+            }
+        };
+        if let Some(trace_offsets) = trace_offsets {
+            reader.trace_count = trace_offsets.len();
+            reader.trace_offsets = Some(trace_offsets);
+        }
+        Ok(reader)
+    }
+
+    /// Returns the trace offset table when the traces have differing lengths,
+    /// e.g. to save it for subsequent opens.
+    ///
+    /// Only a reader made with `open` knows the file's modification time; the
+    /// table of one made with `from_mmap` is always stale.
+    pub fn trace_offsets(&self) -> Option<&TraceOffsets> {
+        self.trace_offsets.as_ref()
+    }
+
+    /// Returns the textual header.
+    pub fn ebcdic_header(&self) -> &EbcdicHeader {
+        &self.ebcdic_header
+    }
+
+    /// Returns the binary header.
+    pub fn binary_header(&self) -> &BinaryHeader {
+        &self.binary_header
+    }
+
+    /// Returns the extended textual headers.
+    pub fn extended_textual_headers(&self) -> &[EbcdicHeader] {
+        &self.extended_textual_headers
+    }
+
+    /// Returns the byte order of the file.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Returns the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn header_mapping(&self) -> &HeaderMapping {
+        &self.header_mapping
+    }
+
+    /// Sets the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn set_header_mapping(&mut self, header_mapping: HeaderMapping) {
+        self.header_mapping = header_mapping;
+    }
+
+    /// Returns how IBM float samples outside the `f32` range are converted.
+    pub fn ibm_policy(&self) -> IbmFloatPolicy {
+        self.ibm_policy
+    }
+
+    /// Sets how IBM float samples outside the `f32` range are converted, e.g. to
+    /// flush denormals to zero or fail on overflow.
+    pub fn set_ibm_policy(&mut self, ibm_policy: IbmFloatPolicy) {
+        self.ibm_policy = ibm_policy;
+    }
+
+    /// Returns whether trace headers keep the bytes they were decoded from.
+    pub fn keep_raw_trace_headers(&self) -> bool {
+        self.keep_raw_trace_headers
+    }
+
+    /// Sets whether trace headers keep the 240 bytes they were decoded from in
+    /// `TraceHeader::raw`, so that `SegyWriter` writes back the bytes of every
+    /// field left unchanged. This costs an allocation per trace, so it is off by
+    /// default.
+    pub fn set_keep_raw_trace_headers(&mut self, keep_raw_trace_headers: bool) {
+        self.keep_raw_trace_headers = keep_raw_trace_headers;
+    }
+
+    /// Returns the number of traces in the file.
+    pub fn trace_count(&self) -> usize {
+        self.trace_count
+    }
+
+    /// Returns the raw 240 bytes of the header of the trace at `index`.
+    pub fn trace_header_bytes(&self, index: usize) -> Result<&[u8], SegyError> {
+        let start = self.trace_offset(index)?;
+        self.slice(start, TRACE_HEADER_SIZE)
+    }
+
+    /// Returns the raw sample bytes of the trace at `index`.
+    pub fn trace_data_bytes(&self, index: usize) -> Result<&[u8], SegyError> {
+        let start = checked_offset(
+            self.trace_offset(index)?,
+            TRACE_HEADER_SIZE + self.layout.extensions_size(),
+        )?;
+        let samples = self.samples_in_trace(index)? as usize;
+        self.slice(start, samples * self.layout.sample_format.sample_size())
+    }
+
+    /// Parses the header of the trace at `index` straight from the map.
+    pub fn trace_header(&self, index: usize) -> Result<TraceHeader, SegyError> {
+        let bytes = self.trace_header_bytes(index)?;
+        let mut header = parse_trace_header(bytes, self.endianness, &self.header_mapping)?;
+        if self.keep_raw_trace_headers {
+            header.raw = bytes.try_into().ok().map(Box::new);
+        }
+        Ok(header)
+    }
+
+    /// Parses the SEG-Y rev2 trace header extension 1 of the trace at `index`, or
+    /// returns `None` when the binary header announces no additional trace headers.
+    pub fn trace_header_extension(
+        &self,
+        index: usize,
+    ) -> Result<Option<TraceHeaderExtension1>, SegyError> {
+        if self.layout.additional_trace_headers == 0 {
+            return Ok(None);
+        }
+        let start = checked_offset(self.trace_offset(index)?, TRACE_HEADER_SIZE)?;
+        parse_trace_header_extension(self.slice(start, TRACE_HEADER_SIZE)?, self.endianness)
+            .map(Some)
+    }
+
+    /// Reads every trace header into a columnar table without touching the samples.
+    pub fn scan_headers(&self) -> Result<HeaderTable, SegyError> {
+        let mut table = HeaderTable::default();
+        for index in 0..self.trace_count {
+            table.push(
+                self.trace_offset(index)? as u64,
+                &self.trace_header(index)?,
+                self.samples_in_trace(index)?,
+            );
+        }
+        Ok(table)
+    }
+
+    /// Decodes the samples of the trace at `index` in their native type.
+    pub fn trace_samples(&self, index: usize) -> Result<TraceSamples, SegyError> {
+        TraceSamples::from_bytes_with_policy(
+            self.trace_data_bytes(index)?,
+            self.layout.sample_format,
+            self.endianness,
+            self.ibm_policy,
+        )
+    }
+
+    /// Reads the trace at `index`.
+    pub fn trace(&self, index: usize) -> Result<Trace, SegyError> {
+        Ok(self.typed_trace(index)?.into())
+    }
+
+    /// Reads the trace at `index`, keeping the samples in their native type.
+    pub fn typed_trace(&self, index: usize) -> Result<TypedTrace, SegyError> {
+        Ok(TypedTrace {
+            header: self.trace_header(index)?,
+            samples: self.trace_samples(index)?,
+        })
+    }
+
+    /// Decodes the samples of the trace at `index` as `f32` into `output`,
+    /// without any intermediate allocation.
+    ///
+    /// `output` must be exactly as long as the trace. IEEE and IBM float data are
+    /// decoded directly from the map; other formats go through `TraceSamples`.
+    pub fn read_trace_f32_into(&self, index: usize, output: &mut [f32]) -> Result<(), SegyError> {
+        let data = self.trace_data_bytes(index)?;
+        let sample_size = self.layout.sample_format.sample_size();
+        if data.len() / sample_size != output.len() {
+            return Err(SegyError::ParseError(format!(
+                "Output holds {} samples but the trace has {}",
+                output.len(),
+                data.len() / sample_size
+            )));
+        }
+        match self.layout.sample_format {
+            SampleFormat::IeeeFloat => {
+                for (value, chunk) in output.iter_mut().zip(data.chunks_exact(4)) {
+                    *value = f32::from_bits(read_u32(chunk, 0, self.endianness)?);
+                }
+            }
+            SampleFormat::IbmFloat => {
+                decode_ibm_slice(data, output, self.endianness, self.ibm_policy)?
+            }
+            _ => {
+                let samples = TraceSamples::from_bytes_with_policy(
+                    data,
+                    self.layout.sample_format,
+                    self.endianness,
+                    self.ibm_policy,
+                )?;
+                output.copy_from_slice(&samples.into_f32());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the samples of the trace at `index` as a slice borrowed from the map.
+    ///
+    /// This only succeeds when the file is in the platform's byte order, `T`
+    /// matches the sample format, and the samples are suitably aligned;
+    /// otherwise `None` is returned and the samples must be decoded instead.
+    pub fn trace_samples_native<T: NativeSample>(
+        &self,
+        index: usize,
+    ) -> Result<Option<&[T]>, SegyError> {
+        if self.endianness != Endianness::native() || T::FORMAT != self.layout.sample_format {
+            return Ok(None);
+        }
+        let data = self.trace_data_bytes(index)?;
+        // Safety: `NativeSample` is only implemented for primitive numeric types,
+        // for which every bit pattern is a valid value.
+        let (prefix, samples, suffix) = unsafe { data.align_to::<T>() };
+        if !prefix.is_empty() || !suffix.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(samples))
+    }
+
+    /// Decodes every trace across the rayon thread pool, returning them in file order.
+    #[cfg(feature = "rayon")]
+    pub fn read_all_traces_parallel(&self) -> Result<Vec<Trace>, SegyError> {
+        (0..self.trace_count)
+            .into_par_iter()
+            .map(|index| self.trace(index))
+            .collect()
+    }
+
+    /// Decodes every trace across the rayon thread pool, keeping the samples in
+    /// their native type and returning the traces in file order.
+    #[cfg(feature = "rayon")]
+    pub fn read_all_typed_traces_parallel(&self) -> Result<Vec<TypedTrace>, SegyError> {
+        (0..self.trace_count)
+            .into_par_iter()
+            .map(|index| self.typed_trace(index))
+            .collect()
+    }
+
+    /// Returns the byte offset of the trace at `index`.
+    fn trace_offset(&self, index: usize) -> Result<usize, SegyError> {
+        if index >= self.trace_count {
+            return Err(SegyError::TraceIndexOutOfRange {
+                index,
+                count: self.trace_count,
+            });
+        }
+        match &self.trace_offsets {
+            Some(trace_offsets) => Ok(trace_offsets.offsets[index] as usize),
+            None => {
+                let relative = index.checked_mul(self.trace_size).ok_or_else(|| {
+                    SegyError::ParseError(format!("Offset of trace {} overflows", index))
+                })?;
+                checked_offset(self.data_offset, relative)
+            }
+        }
+    }
+
+    /// Walks the trace headers in the map to find the offset of every trace.
+    fn scan_trace_offsets(&self) -> Result<TraceOffsets, SegyError> {
+        let file_len = self.mmap.len();
+        let mut offsets = Vec::new();
+        let mut sample_counts = Vec::new();
+        let mut position = self.data_offset;
+        while checked_offset(position, TRACE_HEADER_SIZE)? <= file_len
+            && !self.layout.is_past_last_trace(offsets.len() as u64)
+        {
+            let samples_in_trace = self
+                .layout
+                .samples_in_raw_trace(&self.mmap[position..], self.endianness)?;
+            let next = checked_offset(position, self.layout.trace_size(samples_in_trace) as usize)?;
+            if next > file_len {
+                // Ignore a truncated last trace.
+                break;
+            }
+            offsets.push(position as u64);
+            sample_counts.push(samples_in_trace);
+            position = next;
+        }
+        Ok(TraceOffsets {
+            stamp: FileStamp::from_len(file_len as u64),
+            offsets,
+            sample_counts,
+        })
+    }
+
+    /// Number of samples in the trace at `index`, read from bytes 115-116 of its header.
+    fn samples_in_trace(&self, index: usize) -> Result<u16, SegyError> {
+        let header = self.slice(self.trace_offset(index)?, TRACE_HEADER_SIZE)?;
+        self.layout.samples_in_raw_trace(header, self.endianness)
+    }
+
+    /// Returns `len` bytes of the map starting at `start`.
+    fn slice(&self, start: usize, len: usize) -> Result<&[u8], SegyError> {
+        self.mmap
+            .get(start..checked_offset(start, len)?)
+            .ok_or_else(|| {
+                SegyError::ParseError("Trace extends past the end of the file".to_string())
+            })
+    }
+}
+
+/// Adds `len` to the byte offset `start`, failing instead of overflowing when a
+/// corrupt header gives an offset near the end of the address space.
+fn checked_offset(start: usize, len: usize) -> Result<usize, SegyError> {
+    start
+        .checked_add(len)
+        .ok_or_else(|| SegyError::ParseError(format!("Byte offset {} + {} overflows", start, len)))
+}
+
+/// Sample types that can be borrowed straight from a memory-mapped file,
+/// see `MmapSegyReader::trace_samples_native`.
+pub trait NativeSample: Copy + sealed::Sealed {
+    /// The sample format whose samples have this in-memory representation.
+    const FORMAT: SampleFormat;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! native_sample {
+    ($($ty:ty => $format:ident),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $ty {}
+            impl NativeSample for $ty {
+                const FORMAT: SampleFormat = SampleFormat::$format;
+            }
+        )*
+    };
+}
+
+native_sample! {
+    f32 => IeeeFloat,
+    f64 => IeeeDouble,
+    i8 => Int8,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    u8 => UInt8,
+    u16 => UInt16,
+    u32 => UInt32,
+    u64 => UInt64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{junk_rev1_file, sample_traces, temp_file, write_file};
+
+    #[test]
+    fn rev1_ignores_junk_in_rev2_fields() {
+        let traces = sample_traces(5, 4);
+        let path = temp_file("junk-rev1.sgy", &junk_rev1_file(&traces));
+        let reader = MmapSegyReader::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reader.trace_count(), 5);
+        assert_eq!(reader.data_offset, 3600);
+        assert_eq!(
+            reader.trace(4).unwrap().data_samples,
+            traces[4].data_samples
+        );
+    }
+
+    #[test]
+    fn saved_trace_offsets_are_reused() {
+        let mut traces = sample_traces(4, 6);
+        traces[2].data_samples.truncate(3);
+        traces[2].header.trace_sample_count = 3;
+        let path = temp_file(
+            "saved-offsets.sgy",
+            &write_file(SampleFormat::IeeeFloat, &traces),
+        );
+        let sidecar = TraceOffsets::sidecar_path(&path);
+
+        let reader = MmapSegyReader::open(&path).unwrap();
+        let trace_offsets = reader.trace_offsets().unwrap();
+        assert_eq!(trace_offsets.stamp, FileStamp::of(&path).unwrap());
+        trace_offsets.save(&sidecar).unwrap();
+
+        let trace_offsets = TraceOffsets::load(&sidecar).unwrap();
+        let reader = MmapSegyReader::open_with_trace_offsets(&path, trace_offsets).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&sidecar).unwrap();
+
+        assert_eq!(reader.trace_count(), 4);
+        assert_eq!(reader.trace(2).unwrap(), traces[2]);
+        assert_eq!(reader.trace(3).unwrap(), traces[3]);
+    }
+
+    #[test]
+    fn overflowing_rev2_offsets_are_errors() {
+        let traces = sample_traces(5, 4);
+        let mut bytes = junk_rev1_file(&traces);
+        bytes[3500] = 2; // byte 3501
+        bytes[3502..3504].copy_from_slice(&1u16.to_be_bytes()); // bytes 3503-3504
+        bytes[3506..3512].fill(0); // bytes 3507-3512
+        bytes[3512..3528].fill(0xFF); // bytes 3513-3528
+        let path = temp_file("overflow-rev2.sgy", &bytes);
+        let reader = MmapSegyReader::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The first trace would start past the end of the file.
+        assert_eq!(reader.trace_count(), 0);
+        assert!(reader.trace(0).is_err());
+
+        // Without the fixed-length flag the traces are scanned on open.
+        bytes[3502..3504].fill(0);
+        let path = temp_file("overflow-rev2-variable.sgy", &bytes);
+        let reader = MmapSegyReader::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reader.trace_count(), 0);
+    }
+
+    #[test]
+    fn rev2_trace_counts_are_capped_at_the_file_length() {
+        let traces = sample_traces(5, 60);
+        let mut bytes = junk_rev1_file(&traces);
+        bytes[3500] = 2; // byte 3501
+        bytes[3502..3504].copy_from_slice(&1u16.to_be_bytes()); // bytes 3503-3504
+        bytes[3506..3512].fill(0); // bytes 3507-3512
+        bytes[3512..3520].fill(0xFF); // bytes 3513-3520
+        bytes[3520..3528].copy_from_slice(&3600u64.to_be_bytes()); // bytes 3521-3528
+        let path = temp_file("overcount-rev2.sgy", &bytes);
+        let reader = MmapSegyReader::open(&path).unwrap();
+        let mut segy_reader = crate::open_segy_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Five 480-byte traces hold at most ten 240-byte trace headers.
+        assert_eq!(reader.trace_count(), 10);
+        assert_eq!(segy_reader.trace_count().unwrap(), 10);
+        assert_eq!(
+            reader.trace(4).unwrap().data_samples,
+            traces[4].data_samples
+        );
+        assert!(reader.trace(5).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "rayon"))]
+mod parallel_tests {
+    use super::*;
+    use crate::testing::{sample_traces, temp_file, write_file};
+
+    #[test]
+    fn parallel_reads_match_sequential_reads() {
+        let mut traces = sample_traces(40, 9);
+        // Every third trace is shorter, so the traces are found through a trace offset table.
+        for trace in traces.iter_mut().skip(1).step_by(3) {
+            trace.data_samples.truncate(5);
+            trace.header.trace_sample_count = 5;
+        }
+        for sample_format in [SampleFormat::IbmFloat, SampleFormat::Int16] {
+            let path = temp_file("parallel.sgy", &write_file(sample_format, &traces));
+            let reader = MmapSegyReader::open(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert!(reader.trace_offsets().is_some());
+
+            let count = reader.trace_count();
+            assert_eq!(count, traces.len());
+            let sequential: Vec<Trace> = (0..count).map(|i| reader.trace(i).unwrap()).collect();
+            assert_eq!(reader.read_all_traces_parallel().unwrap(), sequential);
+            let typed: Vec<TypedTrace> =
+                (0..count).map(|i| reader.typed_trace(i).unwrap()).collect();
+            assert_eq!(reader.read_all_typed_traces_parallel().unwrap(), typed);
+        }
+    }
+}
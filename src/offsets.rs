@@ -0,0 +1,174 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    errors::SegyError,
+    open_segy_file,
+    sidecar::{sidecar_path, FileStamp, SidecarReader, SidecarWriter},
+};
+
+/// Magic bytes at the start of a saved trace offset table.
+const OFFSETS_MAGIC: &[u8; 8] = b"SGYOFF02";
+
+/// The byte offset and sample count of every trace in a SEG-Y file.
+///
+/// Built by scanning the trace headers once, it allows random access to files
+/// whose traces have differing lengths, and can be saved next to the SEG-Y file
+/// and loaded again on subsequent opens. Like a `TraceIndex`, it records the
+/// file's size and modification time so that a stale table is detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceOffsets {
+    /// Stamp of the SEG-Y file the table was built from, used to detect stale
+    /// tables. Tables built by a reader that does not know the file's path only
+    /// record its length, and are always stale according to `is_stale`.
+    pub stamp: FileStamp,
+    /// Byte offset of each trace header, in file order.
+    pub offsets: Vec<u64>,
+    /// Number of samples in each trace.
+    pub sample_counts: Vec<u16>,
+}
+
+impl TraceOffsets {
+    /// Returns the number of traces in the table.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns true if the table holds no traces.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Scans the trace headers of the SEG-Y file at `path` and builds its table.
+    pub fn build<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        let stamp = FileStamp::of(&path)?;
+        let mut trace_offsets = open_segy_file(&path)?.build_trace_offsets()?;
+        trace_offsets.stamp = stamp;
+        Ok(trace_offsets)
+    }
+
+    /// Loads the sidecar table of the SEG-Y file at `path`, or builds it and saves
+    /// it when it is missing, unreadable or stale.
+    pub fn load_or_build<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        let sidecar = Self::sidecar_path(&path);
+        if let Ok(trace_offsets) = Self::load(&sidecar) {
+            if !trace_offsets.is_stale(&path)? {
+                return Ok(trace_offsets);
+            }
+        }
+        let trace_offsets = Self::build(&path)?;
+        trace_offsets.save(&sidecar)?;
+        Ok(trace_offsets)
+    }
+
+    /// Returns true if the SEG-Y file at `path` changed since the table was built.
+    pub fn is_stale<P: AsRef<Path>>(&self, path: P) -> Result<bool, SegyError> {
+        Ok(FileStamp::of(path)? != self.stamp)
+    }
+
+    /// Returns the conventional location of the table for a SEG-Y file,
+    /// i.e. the SEG-Y path with `.offsets` appended.
+    pub fn sidecar_path<P: AsRef<Path>>(segy_path: P) -> PathBuf {
+        sidecar_path(segy_path, ".offsets")
+    }
+
+    /// Serializes the table in a compact little-endian binary format.
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), SegyError> {
+        let mut writer = SidecarWriter::new(writer, OFFSETS_MAGIC)?;
+        writer.stamp(&self.stamp)?;
+        writer.u64(self.offsets.len() as u64)?;
+        for (&offset, &count) in self.offsets.iter().zip(&self.sample_counts) {
+            writer.u64(offset)?;
+            writer.u16(count)?;
+        }
+        writer.finish()
+    }
+
+    /// Deserializes a table written by `write_to`.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self, SegyError> {
+        let mut reader = SidecarReader::new(reader, OFFSETS_MAGIC, "trace offset table")?;
+        let stamp = reader.stamp()?;
+        let count = reader.u64()?;
+
+        let mut offsets = Vec::new();
+        let mut sample_counts = Vec::new();
+        for _ in 0..count {
+            offsets.push(reader.u64()?);
+            sample_counts.push(reader.u16()?);
+        }
+        Ok(TraceOffsets {
+            stamp,
+            offsets,
+            sample_counts,
+        })
+    }
+
+    /// Saves the table to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SegyError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Loads a table saved with `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::OpenOptions,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        mmap::MmapSegyReader,
+        testing::{sample_traces, temp_file, write_file},
+    };
+
+    /// Sets the modification time of the file at `path` to `secs` after the epoch.
+    fn touch(path: &Path, secs: u64) {
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn table_is_stale_when_the_file_changes_but_keeps_its_length() {
+        let traces = sample_traces(4, 3);
+        let path = temp_file("offsets.sgy", &write_file(SampleFormat::IbmFloat, &traces));
+        touch(&path, 1_000_000);
+        let trace_offsets = TraceOffsets::load_or_build(&path).unwrap();
+        assert_eq!(trace_offsets.len(), 4);
+        assert!(!trace_offsets.is_stale(&path).unwrap());
+        assert_eq!(
+            TraceOffsets::load(TraceOffsets::sidecar_path(&path)).unwrap(),
+            trace_offsets
+        );
+
+        // Rewrite the file with traces of another length but the same size.
+        std::fs::write(
+            &path,
+            write_file(SampleFormat::IbmFloat, &sample_traces(3, 24)),
+        )
+        .unwrap();
+        touch(&path, 2_000_000);
+        assert_eq!(FileStamp::of(&path).unwrap().len, trace_offsets.stamp.len);
+        assert!(trace_offsets.is_stale(&path).unwrap());
+        assert!(MmapSegyReader::open_with_trace_offsets(&path, trace_offsets).is_err());
+
+        let rebuilt = TraceOffsets::load_or_build(&path).unwrap();
+        assert_eq!(rebuilt.len(), 3);
+        assert_eq!(rebuilt.sample_counts, [24, 24, 24]);
+        let mut reader = crate::open_segy_file(&path).unwrap();
+        assert_eq!(reader.load_or_build_trace_offsets(&path).unwrap(), &rebuilt);
+
+        std::fs::remove_file(TraceOffsets::sidecar_path(&path)).unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,102 +1,193 @@
-use std::io::{self, Read, Seek};
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
  use ebcdic::ebcdic::Ebcdic;
 use crate::{
-    ebcdic_syg::{BinaryHeader, EbcdicHeader, Trace, TraceHeader},
+    ebcdic_syg::{
+        BinaryHeader, EbcdicHeader, Trace, TraceHeader, TraceHeaderExtension1, TypedTrace,
+    },
     errors::SegyError,
-    format::SampleFormat,
-    utils::{ibm_to_ieee_f32, read_i16_be, read_i32_be, read_u16_be},
+    format::{Endianness, SampleFormat},
+    mapping::HeaderMapping,
+    offsets::TraceOffsets,
+    samples::TraceSamples,
+    scan::HeaderTable,
+    sidecar::FileStamp,
+    utils::{
+        read_f64, read_i16, read_i32, read_i64, read_u16, read_u32, read_u64, IbmFloatPolicy,
+    },
     value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
 };
 
 pub struct SegyReader<R: Read + Seek> {
-  
     reader: R,
+    /// Byte order used to decode binary values, detected from the binary header.
+    endianness: Endianness,
+    /// The textual header, once read by `open`.
+    ebcdic_header: Option<EbcdicHeader>,
+    /// The most recently read binary header.
+    binary_header: Option<BinaryHeader>,
+    /// The extended textual headers, once read.
+    extended_textual_headers: Vec<EbcdicHeader>,
+    /// Byte offset of the first trace, known once the extended headers are read.
+    data_offset: Option<u64>,
+    /// Size in bytes of every trace, once the traces are known to be fixed-length.
+    fixed_trace_size: Option<u64>,
+    /// Offsets of every trace, used for random access to variable-length traces.
+    trace_offsets: Option<TraceOffsets>,
+    /// Length of the file, once known; the file is not expected to change while read.
+    file_len: Option<u64>,
+    /// Number of fixed-length traces, once counted.
+    trace_count: Option<usize>,
+    /// Locations of the in-line, cross-line and CDP coordinate trace header fields.
+    header_mapping: HeaderMapping,
+    /// How IBM float samples outside the `f32` range are converted.
+    ibm_policy: IbmFloatPolicy,
+    /// Whether parsed trace headers keep their bytes in `TraceHeader::raw`.
+    keep_raw_trace_headers: bool,
 }
 
-
 impl<R: Read + Seek> SegyReader<R> {
+    /// Creates a new `SegyReader` from any `Read + Seek` source.
+    pub fn new(reader: R) -> Self {
+        SegyReader {
+            reader,
+            endianness: Endianness::Big,
+            ebcdic_header: None,
+            binary_header: None,
+            extended_textual_headers: Vec::new(),
+            data_offset: None,
+            fixed_trace_size: None,
+            trace_offsets: None,
+            file_len: None,
+            trace_count: None,
+            header_mapping: HeaderMapping::default(),
+            ibm_policy: IbmFloatPolicy::default(),
+            keep_raw_trace_headers: false,
+        }
+    }
 
- fn is_probably_ascii(buffer: &[u8]) -> bool {
-        let mut ascii_count = 0;
-        let total = buffer.len();
+    /// Creates a `SegyReader` and reads the textual, binary and extended textual
+    /// headers up front, leaving the source positioned at the first trace.
+    pub fn open(reader: R) -> Result<Self, SegyError> {
+        let mut segy_reader = SegyReader::new(reader);
+        segy_reader.reader.seek(SeekFrom::Start(0))?;
 
-        for &b in buffer {
-            // Check if b is a standard ASCII printable character (0x20..=0x7E),
-            // or a few allowed control chars like \n (0x0A), \r (0x0D), tab (0x09).
-            if (b >= 0x20 && b <= 0x7E) || b == b'\n' || b == b'\r' || b == b'\t' {
-                ascii_count += 1;
-            }
-        }
+        let ebcdic_header = segy_reader.read_ebcdic_header()?;
+        let binary_header = segy_reader.read_binary_header()?;
+        segy_reader.read_extended_textual_headers(&binary_header)?;
+        segy_reader.ebcdic_header = Some(ebcdic_header);
+        Ok(segy_reader)
+    }
 
-        // If more than 80% of the characters appear to be in the valid ASCII range,
-        // we assume it is ASCII. Adjust threshold as needed.
-        let ratio = ascii_count as f32 / total as f32;
-        ratio > 0.80
+    /// Returns the textual header read by `open`.
+    pub fn ebcdic_header(&self) -> Option<&EbcdicHeader> {
+        self.ebcdic_header.as_ref()
     }
-    /// Creates a new `SegyReader` from any `Read + Seek` source.
-    pub fn new(reader: R) -> Self {
-        SegyReader { reader }
+
+    /// Returns the most recently read binary header.
+    pub fn binary_header(&self) -> Option<&BinaryHeader> {
+        self.binary_header.as_ref()
+    }
+
+    /// Returns the extended textual headers read so far.
+    pub fn extended_textual_headers(&self) -> &[EbcdicHeader] {
+        &self.extended_textual_headers
+    }
+
+    /// Returns the byte order used to decode headers and samples.
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    /// Overrides the byte order, e.g. for files where detection is ambiguous.
+    ///
+    /// Note that `read_binary_header` detects and sets the byte order again.
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    /// Returns the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn header_mapping(&self) -> &HeaderMapping {
+        &self.header_mapping
+    }
+
+    /// Sets the trace header locations used for in-line, cross-line and CDP X/Y,
+    /// e.g. `HeaderMapping::field_record_ensemble()` for files with in-line at byte 9.
+    pub fn set_header_mapping(&mut self, header_mapping: HeaderMapping) {
+        self.header_mapping = header_mapping;
+    }
+
+    /// Returns how IBM float samples outside the `f32` range are converted.
+    pub fn ibm_policy(&self) -> IbmFloatPolicy {
+        self.ibm_policy
+    }
+
+    /// Sets how IBM float samples outside the `f32` range are converted, e.g. to
+    /// flush denormals to zero or fail on overflow.
+    pub fn set_ibm_policy(&mut self, ibm_policy: IbmFloatPolicy) {
+        self.ibm_policy = ibm_policy;
+    }
+
+    /// Returns whether trace headers keep the bytes they were decoded from.
+    pub fn keep_raw_trace_headers(&self) -> bool {
+        self.keep_raw_trace_headers
+    }
+
+    /// Sets whether trace headers keep the 240 bytes they were decoded from in
+    /// `TraceHeader::raw`, so that `SegyWriter` writes back the bytes of every
+    /// field left unchanged. This costs an allocation per trace, so it is off by
+    /// default.
+    pub fn set_keep_raw_trace_headers(&mut self, keep_raw_trace_headers: bool) {
+        self.keep_raw_trace_headers = keep_raw_trace_headers;
     }
 
     /// Reads the 3200-byte EBCDIC textual header.
-  
     pub fn read_ebcdic_header(&mut self) -> Result<EbcdicHeader, SegyError> {
         // 1. Read the raw 3200 bytes from the file into `buffer`.
         let mut buffer = vec![0u8; EBCDIC_HEADER_SIZE];
         self.reader.read_exact(&mut buffer)?;
 
-        // 2. Prepare a second buffer for the ASCII output if needed.
-        let mut ascii_buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+        Ok(decode_textual_header(&buffer))
+    }
 
-        // 3. Decide if it's likely ASCII or EBCDIC.
-        //    If it's ASCII, we just copy the buffer; otherwise, we convert.
-        if Self::is_probably_ascii(&buffer) {
-            // Likely ASCII: copy the buffer directly.
-            ascii_buffer.copy_from_slice(&buffer);
-        } else {
-            // Likely EBCDIC: convert to ASCII.
-            Ebcdic::ebcdic_to_ascii(
-                &buffer,             // src
-                &mut ascii_buffer,   // dest
-                EBCDIC_HEADER_SIZE,  // number of bytes to convert
-                true,                // non_printable_to_space
-                true,                // nel_to_lf
-            );
+    /// Reads the extended 3200-byte textual file headers that follow the binary header.
+    ///
+    /// A count of -1 in the binary header means the number of records is variable and
+    /// the last one contains a `((SEG: EndText))` stanza.
+    pub fn read_extended_textual_headers(
+        &mut self,
+        binary_header: &BinaryHeader,
+    ) -> Result<Vec<EbcdicHeader>, SegyError> {
+        let mut headers = ExtendedTextualHeaders::new(binary_header.extended_textual_headers)?;
+        let mut buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+        while !headers.is_complete() {
+            self.reader.read_exact(&mut buffer)?;
+            headers.push(&buffer);
         }
-
-        // 4. Convert that ASCII buffer into a Rust String (lossy to avoid errors
-        //    with unexpected byte values).
-        let text = String::from_utf8_lossy(&ascii_buffer).into_owned();
-
-        // 5. Return the EbcdicHeader struct with the final text.
-        Ok(EbcdicHeader { text })
+        let headers = headers.into_headers();
+        self.extended_textual_headers = headers.clone();
+        self.data_offset = Some(self.reader.stream_position()?);
+        Ok(headers)
     }
 
-    /// Reads the 400-byte binary header.
-    /// Only certain fields are parsed as an example; you can parse more if you wish.
+    /// Reads the 400-byte binary header, decoding every rev1/rev2 field.
+    ///
+    /// The byte order of the file is detected here and used for everything read afterwards.
     pub(crate) fn read_binary_header(&mut self) -> Result<BinaryHeader, SegyError> {
         let mut buffer = vec![0u8; BINARY_HEADER_SIZE];
         self.reader.read_exact(&mut buffer)?;
 
-       
-
-        let job_id = read_i32_be(&buffer, 0)?; // bytes 3201-3204 -> offset 0
-        let line_number = read_i32_be(&buffer, 4)?; // bytes 3205-3208 -> offset 4
-        let reel_number = read_i32_be(&buffer, 8)?; // bytes 3209-3212 -> offset 8
-
-        let sample_interval_us = read_u16_be(&buffer, 16)?; // bytes 3217-3218 -> offset 16
-        let samples_per_trace = read_u16_be(&buffer, 20)?; // bytes 3221-3222 -> offset 20
-        let format_code_raw = read_u16_be(&buffer, 24)?; // bytes 3225-3226 -> offset 24
-        let sample_format_code = SampleFormat::from_code(format_code_raw)?;
-
-        Ok(BinaryHeader {
-            job_id,
-            line_number,
-            reel_number,
-            sample_format_code,
-            samples_per_trace,
-            sample_interval_us,
-        })
+        self.endianness = detect_endianness(&buffer);
+        let binary_header = parse_binary_header(&buffer, self.endianness)?;
+        self.binary_header = Some(binary_header.clone());
+        self.data_offset = None;
+        self.fixed_trace_size = None;
+        self.trace_offsets = None;
+        self.file_len = None;
+        self.trace_count = None;
+        Ok(binary_header)
     }
 
     /// Reads one SEG-Y trace (header + data).
@@ -105,238 +196,1246 @@ impl<R: Read + Seek> SegyReader<R> {
         sample_format: SampleFormat,
         default_samples_per_trace: u16,
     ) -> Result<Trace, SegyError> {
+        Ok(self
+            .read_typed_trace(sample_format, default_samples_per_trace)?
+            .into())
+    }
+
+    /// Reads one SEG-Y trace, keeping the samples in their native type.
+    pub fn read_typed_trace(
+        &mut self,
+        sample_format: SampleFormat,
+        default_samples_per_trace: u16,
+    ) -> Result<TypedTrace, SegyError> {
         let header = self.read_trace_header()?;
+        self.skip_trace_header_extensions()?;
         let samples_in_trace = if header.trace_sample_count == 0 {
             default_samples_per_trace
         } else {
             header.trace_sample_count
         };
 
-        let data_samples = self.read_trace_data(sample_format, samples_in_trace)?;
-        Ok(Trace {
-            header,
-            data_samples,
-        })
+        let samples = self.read_trace_samples(sample_format, samples_in_trace)?;
+        Ok(TypedTrace { header, samples })
     }
 
-    /// Reads the fixed 240-byte trace header, parsing key fields into a `TraceHeader` struct.
+    /// Reads the fixed 240-byte trace header, parsing every field into a `TraceHeader` struct.
+    ///
+    /// Any SEG-Y rev2 trace header extensions that follow it are left unread.
     pub fn read_trace_header(&mut self) -> Result<TraceHeader, SegyError> {
-        let mut buffer = vec![0u8; TRACE_HEADER_SIZE];
+        let mut buffer = [0u8; TRACE_HEADER_SIZE];
         self.reader.read_exact(&mut buffer)?;
-
-        // Original fields you had:
-        let trace_sequence_line = read_i32_be(&buffer, 0)?; // bytes 1-4
-        let field_record_number = read_i32_be(&buffer, 8)?; // bytes 9-12
-        let trace_number = read_i32_be(&buffer, 12)?; // bytes 13-16
-        let source_point_number = read_i32_be(&buffer, 16)?; // bytes 17-20
-        let trace_sample_count = read_u16_be(&buffer, 114)?; // bytes 115-116
-        let trace_sample_interval_us = read_u16_be(&buffer, 116)?; // bytes 117-118
-
-        // Additional fields:
-        let trace_sequence_file = read_i32_be(&buffer, 4)?; // bytes 5-8
-        let ensemble_number = read_i32_be(&buffer, 20)?; // bytes 21-24
-        let trace_in_ensemble = read_i32_be(&buffer, 24)?; // bytes 25-28
-        let offset = read_i32_be(&buffer, 36)?; // bytes 37-40
-        let coord_scalar = read_i16_be(&buffer, 70)?; // bytes 71-72
-        let cdp_x = read_i32_be(&buffer, 72)?; // bytes 73-76
-        let cdp_y = read_i32_be(&buffer, 76)?; // bytes 77-80
-        let year_data_recorded = read_u16_be(&buffer, 158)?; // bytes 159-160
-        let day_of_year = read_u16_be(&buffer, 160)?; // bytes 161-162
-        let hour_of_day = read_u16_be(&buffer, 162)?; // bytes 163-164
-        let minute_of_hour = read_u16_be(&buffer, 164)?; // bytes 165-166
-        let second_of_minute = read_u16_be(&buffer, 166)?; // bytes 167-168
-
-        Ok(TraceHeader {
-            trace_sequence_line,
-            field_record_number,
-            trace_number,
-            source_point_number,
-            trace_sample_count,
-            trace_sample_interval_us,
-            trace_sequence_file,
-            ensemble_number,
-            trace_in_ensemble,
-            offset,
-            cdp_x,
-            cdp_y,
-            coord_scalar,
-            year_data_recorded,
-            day_of_year,
-            hour_of_day,
-            minute_of_hour,
-            second_of_minute,
-        })
+        self.parse_trace_header(buffer)
     }
 
-    /// Reads the trace sample data, converting to `f32` as needed.
-    fn read_trace_data(
+    /// Reads the trace sample data in the native type of the sample format.
+    pub fn read_trace_samples(
         &mut self,
         sample_format: SampleFormat,
         samples_in_trace: u16,
-    ) -> Result<Vec<f32>, SegyError> {
+    ) -> Result<TraceSamples, SegyError> {
         let sample_count = samples_in_trace as usize;
-        let sample_size = match sample_format {
-            SampleFormat::IbmFloat => 4,
-            SampleFormat::Int32 => 4,
-            SampleFormat::Int16 => 2,
-            SampleFormat::IeeeFloat => 4,
-            SampleFormat::Int8 => 1,
+        let mut buffer = vec![0u8; sample_count * sample_format.sample_size()];
+        self.reader.read_exact(&mut buffer)?;
+
+        TraceSamples::from_bytes_with_policy(
+            &buffer,
+            sample_format,
+            self.endianness,
+            self.ibm_policy,
+        )
+    }
+
+    /// Reads all traces from the current file position until EOF.
+    pub fn read_all_traces(
+        &mut self,
+        binary_header: &BinaryHeader,
+    ) -> Result<Vec<Trace>, SegyError> {
+        Traces {
+            layout: TraceLayout::from(binary_header),
+            reader: self,
+            traces_read: 0,
+            finished: false,
+        }
+        .collect()
+    }
+
+    /// Returns an iterator that reads one trace at a time from the current file
+    /// position until EOF, so files of any size can be processed in constant memory.
+    ///
+    /// When a rev2 binary header gives the number of traces (bytes 3513-3520), at
+    /// most that many are read, so a data trailer is not mistaken for traces.
+    ///
+    /// The binary header must have been read first, e.g. with `SegyReader::open`.
+    pub fn traces(&mut self) -> Result<Traces<'_, R>, SegyError> {
+        Ok(Traces {
+            layout: self.layout()?,
+            reader: self,
+            traces_read: 0,
+            finished: false,
+        })
+    }
+
+    /// Returns an iterator over the trace headers from the current file position
+    /// until EOF, seeking over the trace header extensions and sample data instead
+    /// of reading them.
+    ///
+    /// Like `traces`, it stops after the number of traces given by a rev2 binary header.
+    ///
+    /// The binary header must have been read first, e.g. with `SegyReader::open`.
+    pub fn trace_headers(&mut self) -> Result<TraceHeaders<'_, R>, SegyError> {
+        Ok(TraceHeaders {
+            layout: self.layout()?,
+            reader: self,
+            traces_read: 0,
+            finished: false,
+        })
+    }
+
+    /// Returns the number of traces in the file.
+    ///
+    /// This uses the trace offset table when one is set, then the rev2 trace count
+    /// from the binary header, then the length of the trace data divided by the
+    /// fixed trace length. For variable-length traces the offset table is built.
+    ///
+    /// The count is cached, so random access does not seek to the end of the file
+    /// for every trace.
+    pub fn trace_count(&mut self) -> Result<usize, SegyError> {
+        if let Some(trace_offsets) = &self.trace_offsets {
+            return Ok(trace_offsets.len());
+        }
+        if let Some(trace_count) = self.trace_count {
+            return Ok(trace_count);
+        }
+        let layout = self.layout()?;
+        let trace_count = if layout.traces_in_file > 0 {
+            let data_offset = self.data_offset()?;
+            let file_len = self.file_len()?;
+            layout.announced_traces(file_len.saturating_sub(data_offset)) as usize
+        } else {
+            match self.fixed_trace_size()? {
+                Some(trace_size) => {
+                    let data_offset = self.data_offset()?;
+                    let file_len = self.file_len()?;
+                    (file_len.saturating_sub(data_offset) / trace_size) as usize
+                }
+                None => return Ok(self.ensure_trace_offsets()?.len()),
+            }
         };
+        self.trace_count = Some(trace_count);
+        Ok(trace_count)
+    }
+
+    /// Drops what is known about the positions of the traces, e.g. after a trace
+    /// header edit changed the length of a trace.
+    pub(crate) fn forget_trace_positions(&mut self) {
+        self.fixed_trace_size = None;
+        self.trace_offsets = None;
+        self.trace_count = None;
+    }
+
+    /// Returns the length of the file, seeking to its end the first time only.
+    fn file_len(&mut self) -> Result<u64, SegyError> {
+        if let Some(file_len) = self.file_len {
+            return Ok(file_len);
+        }
+        let file_len = self.reader.seek(SeekFrom::End(0))?;
+        self.file_len = Some(file_len);
+        Ok(file_len)
+    }
+
+    /// Reads the trace at `index` (counting from zero) by seeking directly to it.
+    ///
+    /// This moves the file position to the end of that trace.
+    pub fn trace(&mut self, index: usize) -> Result<Trace, SegyError> {
+        Ok(self.typed_trace(index)?.into())
+    }
 
-        let mut buffer = vec![0u8; sample_count * sample_size];
+    /// Reads the trace at `index`, keeping the samples in their native type.
+    pub fn typed_trace(&mut self, index: usize) -> Result<TypedTrace, SegyError> {
+        let layout = self.layout()?;
+        self.seek_trace(index)?;
+        self.read_typed_trace(layout.sample_format, layout.default_samples_per_trace)
+    }
+
+    /// Reads the header of the trace at `index` without reading its samples.
+    pub fn trace_header(&mut self, index: usize) -> Result<TraceHeader, SegyError> {
+        self.seek_trace(index)?;
+        self.read_trace_header()
+    }
+
+    /// Reads the SEG-Y rev2 trace header extension 1 of the trace at `index`, or
+    /// `None` when the binary header announces no additional trace headers.
+    pub fn trace_header_extension(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<TraceHeaderExtension1>, SegyError> {
+        if self.layout()?.additional_trace_headers == 0 {
+            return Ok(None);
+        }
+        self.seek_trace(index)?;
+        self.reader.seek(SeekFrom::Current(TRACE_HEADER_SIZE as i64))?;
+        let mut buffer = [0u8; TRACE_HEADER_SIZE];
         self.reader.read_exact(&mut buffer)?;
+        parse_trace_header_extension(&buffer, self.endianness).map(Some)
+    }
 
-        let mut data_samples = Vec::with_capacity(sample_count);
-        match sample_format {
-            SampleFormat::IbmFloat => {
-                for chunk in buffer.chunks_exact(4) {
-                    let val = ibm_to_ieee_f32(chunk).ok_or(SegyError::IbmFloatConversionError)?;
-                    data_samples.push(val);
-                }
+    /// Reads the SEG-Y rev2 data trailer, the 3200-byte textual records that
+    /// follow the last trace (bytes 3529-3532 give their number). Files older
+    /// than rev2 have none.
+    ///
+    /// A count of -1 means the number of records is variable, and they are read up
+    /// to the one containing a `((SEG: EndText))` stanza or the end of the file.
+    pub fn read_trailer_records(&mut self) -> Result<Vec<EbcdicHeader>, SegyError> {
+        let binary_header = self.stored_binary_header()?;
+        if binary_header.segy_revision_major < 2 {
+            return Ok(Vec::new());
+        }
+        let count = binary_header.trailer_stanza_records;
+        let record_size = EBCDIC_HEADER_SIZE as u64;
+        let file_len = self.file_len()?;
+        let start = match count {
+            0 => return Ok(Vec::new()),
+            count if count > 0 => file_len
+                .checked_sub(count as u64 * record_size)
+                .ok_or_else(|| {
+                    SegyError::ParseError(format!(
+                        "File is too short for {} trailer records",
+                        count
+                    ))
+                })?,
+            -1 => self.end_of_traces()?,
+            other => {
+                return Err(SegyError::ParseError(format!(
+                    "Invalid number of trailer records: {}",
+                    other
+                )))
             }
-            SampleFormat::Int32 => {
-                for chunk in buffer.chunks_exact(4) {
-                    let val = i32::from_be_bytes(chunk.try_into().unwrap()) as f32;
-                    data_samples.push(val);
-                }
+        };
+
+        self.reader.seek(SeekFrom::Start(start))?;
+        let mut records = Vec::new();
+        let mut position = start;
+        while position + record_size <= file_len {
+            let record = self.read_ebcdic_header()?;
+            let is_last = count == -1 && contains_end_text(&record.text);
+            records.push(record);
+            if is_last {
+                break;
             }
-            SampleFormat::Int16 => {
-                for chunk in buffer.chunks_exact(2) {
-                    let val = i16::from_be_bytes(chunk.try_into().unwrap()) as f32;
-                    data_samples.push(val);
-                }
+            position += record_size;
+        }
+        Ok(records)
+    }
+
+    /// Reads the trace whose header starts at byte `offset` of the file,
+    /// e.g. an offset from a `HeaderTable` or `TraceIndex`.
+    pub fn read_trace_at(&mut self, offset: u64) -> Result<Trace, SegyError> {
+        let layout = self.layout()?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.read_trace(layout.sample_format, layout.default_samples_per_trace)
+    }
+
+    /// Reads the single sample at `sample_index` of the trace at `index`, as `f32`,
+    /// seeking over the trace header and the samples before it.
+    pub fn trace_sample(&mut self, index: usize, sample_index: usize) -> Result<f32, SegyError> {
+        let layout = self.layout()?;
+        self.seek_trace(index)?;
+        let samples_in_trace = match &self.trace_offsets {
+            Some(trace_offsets) => trace_offsets.sample_counts[index],
+            None => layout.default_samples_per_trace,
+        };
+        if sample_index >= samples_in_trace as usize {
+            return Err(SegyError::ParseError(format!(
+                "Sample index {} out of range for a trace of {} samples",
+                sample_index, samples_in_trace
+            )));
+        }
+        let sample_size = layout.sample_format.sample_size();
+        self.reader.seek(SeekFrom::Current(
+            (TRACE_HEADER_SIZE + layout.extensions_size() + sample_index * sample_size) as i64,
+        ))?;
+        let mut buffer = vec![0u8; sample_size];
+        self.reader.read_exact(&mut buffer)?;
+        let samples = TraceSamples::from_bytes_with_policy(
+            &buffer,
+            layout.sample_format,
+            self.endianness,
+            self.ibm_policy,
+        )?;
+        Ok(samples.into_f32()[0])
+    }
+
+    /// Returns the byte offset of the first trace.
+    pub fn data_offset(&self) -> Result<u64, SegyError> {
+        let binary_header = self.stored_binary_header()?;
+        let first_trace_offset = TraceLayout::from(binary_header).first_trace_offset;
+        if first_trace_offset > 0 {
+            return Ok(first_trace_offset);
+        }
+        if let Some(data_offset) = self.data_offset {
+            return Ok(data_offset);
+        }
+        match binary_header.extended_textual_headers {
+            count if count >= 0 => Ok((EBCDIC_HEADER_SIZE
+                + BINARY_HEADER_SIZE
+                + count as usize * EBCDIC_HEADER_SIZE) as u64),
+            _ => Err(SegyError::ParseError(
+                "Extended textual headers must be read to locate the traces".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the size in bytes of every trace if all traces have the same length.
+    ///
+    /// Traces are fixed-length when the binary header says so (bytes 3503-3504), or
+    /// when the first trace has the binary header's sample count and the trace data
+    /// is an exact multiple of that trace length.
+    pub fn fixed_trace_size(&mut self) -> Result<Option<u64>, SegyError> {
+        if let Some(size) = self.fixed_trace_size {
+            return Ok(Some(size));
+        }
+        let layout = self.layout()?;
+        let size = layout.trace_size(layout.default_samples_per_trace);
+        if self.stored_binary_header()?.fixed_length_traces != 1 {
+            let data_offset = self.data_offset()?;
+            let file_len = self.file_len()?;
+            let data_len = file_len.saturating_sub(data_offset);
+            if data_len % size != 0 {
+                return Ok(None);
             }
-            SampleFormat::IeeeFloat => {
-                for chunk in buffer.chunks_exact(4) {
-                    let val = f32::from_be_bytes(chunk.try_into().unwrap());
-                    data_samples.push(val);
+            if data_len > 0 {
+                self.reader.seek(SeekFrom::Start(data_offset))?;
+                let first = self.read_trace_header()?;
+                if layout.samples_in_trace(&first) != layout.default_samples_per_trace {
+                    return Ok(None);
                 }
             }
-            SampleFormat::Int8 => {
-                for &byte in buffer.iter() {
-                    let val = byte as i8 as f32;
-                    data_samples.push(val);
-                }
+        }
+        self.fixed_trace_size = Some(size);
+        Ok(Some(size))
+    }
+
+    /// Scans the trace headers from the first trace to the end of the file and
+    /// returns the offset of every trace, seeking over the sample data.
+    ///
+    /// The reader does not know the file's path, so the table is stamped with the
+    /// file length only; use `TraceOffsets::build` for a table to save.
+    pub fn build_trace_offsets(&mut self) -> Result<TraceOffsets, SegyError> {
+        let mut offsets = Vec::new();
+        let mut sample_counts = Vec::new();
+        let file_len = self.scan_trace_headers(|offset, _, samples_in_trace| {
+            offsets.push(offset);
+            sample_counts.push(samples_in_trace);
+        })?;
+        Ok(TraceOffsets {
+            stamp: FileStamp::from_len(file_len),
+            offsets,
+            sample_counts,
+        })
+    }
+
+    /// Reads every trace header of the file into a columnar table, seeking over
+    /// the sample data instead of reading it.
+    pub fn scan_headers(&mut self) -> Result<HeaderTable, SegyError> {
+        let mut table = HeaderTable::default();
+        self.scan_trace_headers(|offset, header, samples_in_trace| {
+            table.push(offset, header, samples_in_trace)
+        })?;
+        Ok(table)
+    }
+
+    /// Walks the trace headers from the first trace to the end of the file,
+    /// calling `visit` with the offset, header and sample count of each trace.
+    ///
+    /// Returns the length of the file.
+    fn scan_trace_headers<F>(&mut self, mut visit: F) -> Result<u64, SegyError>
+    where
+        F: FnMut(u64, &TraceHeader, u16),
+    {
+        let layout = self.layout()?;
+        let data_offset = self.data_offset()?;
+        let file_len = self.file_len()?;
+
+        let mut visited = 0u64;
+        let mut position = data_offset;
+        self.reader.seek(SeekFrom::Start(position))?;
+        while !layout.is_past_last_trace(visited) {
+            let Some(header) = self.next_trace_header()? else {
+                break;
+            };
+            let samples_in_trace = layout.samples_in_trace(&header);
+            let next = position + layout.trace_size(samples_in_trace);
+            if next > file_len {
+                // Ignore a truncated last trace.
+                break;
             }
+            visit(position, &header, samples_in_trace);
+            visited += 1;
+            position = self.reader.seek(SeekFrom::Start(next))?;
         }
-        Ok(data_samples)
+        Ok(file_len)
     }
 
-    /// Reads all traces from the current file position until EOF.
-    pub fn read_all_traces(
+    /// Uses a previously built trace offset table for random access.
+    ///
+    /// The table is rejected if it was built from a file of a different length.
+    /// The reader does not know the file's path, so the modification time is not
+    /// checked; use `TraceOffsets::is_stale` or `load_or_build_trace_offsets` for that.
+    pub fn set_trace_offsets(&mut self, trace_offsets: TraceOffsets) -> Result<(), SegyError> {
+        let file_len = self.file_len()?;
+        if trace_offsets.stamp.len != file_len {
+            return Err(SegyError::ParseError(format!(
+                "Trace offset table was built for a file of {} bytes, not {}",
+                trace_offsets.stamp.len, file_len
+            )));
+        }
+        self.trace_offsets = Some(trace_offsets);
+        Ok(())
+    }
+
+    /// Returns the trace offset table in use, if any.
+    pub fn trace_offsets(&self) -> Option<&TraceOffsets> {
+        self.trace_offsets.as_ref()
+    }
+
+    /// Uses the sidecar trace offset table of the SEG-Y file at `segy_path`, which
+    /// is the file this reader reads, building and saving it when it is missing or
+    /// stale. See `TraceOffsets::load_or_build`.
+    pub fn load_or_build_trace_offsets<P: AsRef<Path>>(
         &mut self,
-        binary_header: &BinaryHeader,
-    ) -> Result<Vec<Trace>, SegyError> {
-        let mut traces = Vec::new();
-        loop {
-            // try to read the next 240-byte trace header.
-            let mut header_buffer = [0u8; TRACE_HEADER_SIZE];
-            match self.reader.read_exact(&mut header_buffer) {
-                Ok(_) => {
-                    // We read a full 240 bytes, so parse that.
-                    let trace_header = {
-                     
-                        let trace_sequence_line = read_i32_be(&header_buffer, 0)?;
-                        let field_record_number = read_i32_be(&header_buffer, 8)?;
-                        let trace_number = read_i32_be(&header_buffer, 12)?;
-                        let source_point_number = read_i32_be(&header_buffer, 16)?;
-                        let trace_sample_count = read_u16_be(&header_buffer, 114)?;
-                        let trace_sample_interval_us = read_u16_be(&header_buffer, 116)?;
-
-                        let trace_sequence_file = read_i32_be(&header_buffer, 4)?;
-                        let ensemble_number = read_i32_be(&header_buffer, 20)?;
-                        let trace_in_ensemble = read_i32_be(&header_buffer, 24)?;
-                        let offset = read_i32_be(&header_buffer, 36)?;
-                        let coord_scalar = read_i16_be(&header_buffer, 70)?;
-                        let cdp_x = read_i32_be(&header_buffer, 72)?;
-                        let cdp_y = read_i32_be(&header_buffer, 76)?;
-                        let year_data_recorded = read_u16_be(&header_buffer, 158)?;
-                        let day_of_year = read_u16_be(&header_buffer, 160)?;
-                        let hour_of_day = read_u16_be(&header_buffer, 162)?;
-                        let minute_of_hour = read_u16_be(&header_buffer, 164)?;
-                        let second_of_minute = read_u16_be(&header_buffer, 166)?;
-
-                        TraceHeader {
-                            trace_sequence_line,
-                            field_record_number,
-                            trace_number,
-                            source_point_number,
-                            trace_sample_count,
-                            trace_sample_interval_us,
-                            trace_sequence_file,
-                            ensemble_number,
-                            trace_in_ensemble,
-                            offset,
-                            cdp_x,
-                            cdp_y,
-                            coord_scalar,
-                            year_data_recorded,
-                            day_of_year,
-                            hour_of_day,
-                            minute_of_hour,
-                            second_of_minute,
-                        }
-                    };
-
-                    let samples_in_trace = if trace_header.trace_sample_count == 0 {
-                        binary_header.samples_per_trace
-                    } else {
-                        trace_header.trace_sample_count
-                    };
-                    let sample_size = binary_header.sample_format_code.sample_size();
-                    let mut buffer = vec![0u8; samples_in_trace as usize * sample_size];
-                    self.reader.read_exact(&mut buffer)?;
-
-                    // Convert the trace samples to f32, as in read_trace_data.
-                    let mut data_samples = Vec::with_capacity(samples_in_trace as usize);
-                    match binary_header.sample_format_code {
-                        SampleFormat::IbmFloat => {
-                            for chunk in buffer.chunks_exact(4) {
-                                let val = ibm_to_ieee_f32(chunk)
-                                    .ok_or(SegyError::IbmFloatConversionError)?;
-                                data_samples.push(val);
-                            }
-                        }
-                        SampleFormat::Int32 => {
-                            for chunk in buffer.chunks_exact(4) {
-                                let val = i32::from_be_bytes(chunk.try_into().unwrap()) as f32;
-                                data_samples.push(val);
-                            }
-                        }
-                        SampleFormat::Int16 => {
-                            for chunk in buffer.chunks_exact(2) {
-                                let val = i16::from_be_bytes(chunk.try_into().unwrap()) as f32;
-                                data_samples.push(val);
-                            }
-                        }
-                        SampleFormat::IeeeFloat => {
-                            for chunk in buffer.chunks_exact(4) {
-                                let val = f32::from_be_bytes(chunk.try_into().unwrap());
-                                data_samples.push(val);
-                            }
-                        }
-                        SampleFormat::Int8 => {
-                            for &byte in buffer.iter() {
-                                data_samples.push(byte as i8 as f32);
-                            }
-                        }
-                    }
-
-                    traces.push(Trace {
-                        header: trace_header,
-                        data_samples,
-                    });
-                }
-                Err(e) => {
-                    // If we can't read exactly 240 bytes, we assume EOF (or partial file).
-                    if e.kind() != io::ErrorKind::UnexpectedEof {
-                        return Err(SegyError::IoError(e));
-                    }
-                    // Graceful EOF
-                    break;
-                }
+        segy_path: P,
+    ) -> Result<&TraceOffsets, SegyError> {
+        self.set_trace_offsets(TraceOffsets::load_or_build(segy_path)?)?;
+        self.ensure_trace_offsets()
+    }
+
+    /// Returns the trace offset table, building it first if needed.
+    fn ensure_trace_offsets(&mut self) -> Result<&TraceOffsets, SegyError> {
+        if self.trace_offsets.is_none() {
+            self.trace_offsets = Some(self.build_trace_offsets()?);
+        }
+        Ok(self.trace_offsets.as_ref().unwrap())
+    }
+
+    /// Returns the byte offset of the header of the trace at `index`.
+    pub fn trace_offset(&mut self, index: usize) -> Result<u64, SegyError> {
+        let count = self.trace_count()?;
+        if index >= count {
+            return Err(SegyError::TraceIndexOutOfRange { index, count });
+        }
+        Ok(if let Some(trace_offsets) = &self.trace_offsets {
+            trace_offsets.offsets[index]
+        } else if let Some(trace_size) = self.fixed_trace_size()? {
+            self.data_offset()? + index as u64 * trace_size
+        } else {
+            self.ensure_trace_offsets()?.offsets[index]
+        })
+    }
+
+    /// Returns a mutable reference to the underlying source.
+    ///
+    /// Moving its position affects where the streaming methods read next.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying source.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Seeks to the start of the trace at `index`.
+    fn seek_trace(&mut self, index: usize) -> Result<(), SegyError> {
+        let offset = self.trace_offset(index)?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    /// Returns the byte offset just past the last trace.
+    fn end_of_traces(&mut self) -> Result<u64, SegyError> {
+        let layout = self.layout()?;
+        match self.trace_count()?.checked_sub(1) {
+            Some(last) => {
+                let header = self.trace_header(last)?;
+                let start = self.reader.stream_position()? - TRACE_HEADER_SIZE as u64;
+                Ok(start + layout.trace_size(layout.samples_in_trace(&header)))
             }
+            None => self.data_offset(),
+        }
+    }
+
+    /// Seeks over the trace header extensions that follow a trace header, when
+    /// the binary header announces any.
+    fn skip_trace_header_extensions(&mut self) -> Result<(), SegyError> {
+        let extensions_size = self.layout().map_or(0, |layout| layout.extensions_size());
+        if extensions_size > 0 {
+            self.reader.seek(SeekFrom::Current(extensions_size as i64))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the stored binary header, failing if it has not been read yet.
+    fn stored_binary_header(&self) -> Result<&BinaryHeader, SegyError> {
+        self.binary_header
+            .as_ref()
+            .ok_or_else(|| SegyError::ParseError("Binary header has not been read".to_string()))
+    }
+
+    /// Returns the trace layout described by the stored binary header.
+    fn layout(&self) -> Result<TraceLayout, SegyError> {
+        self.stored_binary_header().map(TraceLayout::from)
+    }
+
+    /// Parses a trace header with the reader's header mapping, keeping its bytes
+    /// if asked to.
+    fn parse_trace_header(
+        &self,
+        buffer: [u8; TRACE_HEADER_SIZE],
+    ) -> Result<TraceHeader, SegyError> {
+        let mut header = parse_trace_header(&buffer, self.endianness, &self.header_mapping)?;
+        if self.keep_raw_trace_headers {
+            header.raw = Some(Box::new(buffer));
+        }
+        Ok(header)
+    }
+
+    /// Reads the next trace header, returning `None` at the end of the file.
+    fn next_trace_header(&mut self) -> Result<Option<TraceHeader>, SegyError> {
+        let mut header_buffer = [0u8; TRACE_HEADER_SIZE];
+        match self.reader.read_exact(&mut header_buffer) {
+            Ok(_) => Ok(Some(self.parse_trace_header(header_buffer)?)),
+            // If we can't read exactly 240 bytes, we assume EOF (or partial file).
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(SegyError::IoError(e)),
+        }
+    }
+}
+
+/// What is needed from the binary header to walk through the traces.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceLayout {
+    pub(crate) sample_format: SampleFormat,
+    pub(crate) default_samples_per_trace: u16,
+    /// Number of 240-byte trace header extensions after each standard trace header.
+    pub(crate) additional_trace_headers: u32,
+    /// Number of traces announced by a rev2 binary header, zero when unknown.
+    pub(crate) traces_in_file: u64,
+    /// Byte offset of the first trace given by a rev2 binary header, zero when unknown.
+    pub(crate) first_trace_offset: u64,
+}
+
+impl TraceLayout {
+    /// Number of samples in a trace, falling back to the binary header when the
+    /// trace header does not specify it.
+    pub(crate) fn samples_in_trace(&self, header: &TraceHeader) -> u16 {
+        if header.trace_sample_count == 0 {
+            self.default_samples_per_trace
+        } else {
+            header.trace_sample_count
         }
-        Ok(traces)
+    }
+
+    /// Number of samples in a trace whose header is still raw bytes, read from
+    /// bytes 115-116 with the same fallback as `samples_in_trace`.
+    pub(crate) fn samples_in_raw_trace(
+        &self,
+        header: &[u8],
+        endianness: Endianness,
+    ) -> Result<u16, SegyError> {
+        Ok(match read_u16(header, 114, endianness)? {
+            0 => self.default_samples_per_trace,
+            count => count,
+        })
+    }
+
+    /// Returns true once `traces_read` reaches the trace count of a rev2 binary header.
+    pub(crate) fn is_past_last_trace(&self, traces_read: u64) -> bool {
+        self.traces_in_file > 0 && traces_read >= self.traces_in_file
+    }
+
+    /// Number of traces announced by a rev2 binary header, capped at the number of
+    /// header-only traces that `data_len` bytes of trace data can hold, so that a
+    /// corrupt count does not make up traces that are not in the file.
+    pub(crate) fn announced_traces(&self, data_len: u64) -> u64 {
+        self.traces_in_file.min(data_len / self.trace_size(0))
+    }
+
+    /// Size in bytes of the trace header extensions that follow each trace header.
+    pub(crate) fn extensions_size(&self) -> usize {
+        self.additional_trace_headers as usize * TRACE_HEADER_SIZE
+    }
+
+    /// Size in bytes of a trace (headers + data) with the given number of samples.
+    pub(crate) fn trace_size(&self, samples_in_trace: u16) -> u64 {
+        (TRACE_HEADER_SIZE
+            + self.extensions_size()
+            + samples_in_trace as usize * self.sample_format.sample_size()) as u64
+    }
+}
+
+impl From<&BinaryHeader> for TraceLayout {
+    fn from(binary_header: &BinaryHeader) -> Self {
+        // Bytes 3507-3528 are unassigned before rev2, so only trust them from rev2 on.
+        let is_rev2 = binary_header.segy_revision_major >= 2;
+        TraceLayout {
+            sample_format: binary_header.sample_format_code,
+            default_samples_per_trace: binary_header.samples_per_trace,
+            additional_trace_headers: if is_rev2 {
+                binary_header.max_additional_trace_headers
+            } else {
+                0
+            },
+            traces_in_file: if is_rev2 { binary_header.traces_in_file } else { 0 },
+            first_trace_offset: if is_rev2 {
+                binary_header.first_trace_offset
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// Streaming iterator over the traces of a SEG-Y file, see `SegyReader::traces`.
+pub struct Traces<'a, R: Read + Seek> {
+    reader: &'a mut SegyReader<R>,
+    layout: TraceLayout,
+    traces_read: u64,
+    finished: bool,
+}
+
+impl<R: Read + Seek> Iterator for Traces<'_, R> {
+    type Item = Result<Trace, SegyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.layout.is_past_last_trace(self.traces_read) {
+            return None;
+        }
+        let result = match self.reader.next_trace_header() {
+            Ok(Some(header)) => {
+                let samples_in_trace = self.layout.samples_in_trace(&header);
+                let extensions_size = self.layout.extensions_size() as i64;
+                self.reader
+                    .reader
+                    .seek(SeekFrom::Current(extensions_size))
+                    .map_err(SegyError::from)
+                    .and_then(|_| {
+                        self.reader
+                            .read_trace_samples(self.layout.sample_format, samples_in_trace)
+                    })
+                    .map(|samples| Trace {
+                        header,
+                        data_samples: samples.into_f32(),
+                    })
+            }
+            Ok(None) => {
+                self.finished = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        // Stop after the first error rather than trying to resynchronize.
+        self.finished = result.is_err();
+        self.traces_read += 1;
+        Some(result)
+    }
+}
+
+/// Streaming iterator over the trace headers of a SEG-Y file, see `SegyReader::trace_headers`.
+pub struct TraceHeaders<'a, R: Read + Seek> {
+    reader: &'a mut SegyReader<R>,
+    layout: TraceLayout,
+    traces_read: u64,
+    finished: bool,
+}
+
+impl<R: Read + Seek> Iterator for TraceHeaders<'_, R> {
+    type Item = Result<TraceHeader, SegyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.layout.is_past_last_trace(self.traces_read) {
+            return None;
+        }
+        let result = match self.reader.next_trace_header() {
+            Ok(Some(header)) => {
+                let samples_in_trace = self.layout.samples_in_trace(&header) as i64;
+                let data_size = self.layout.extensions_size() as i64
+                    + samples_in_trace * self.layout.sample_format.sample_size() as i64;
+                self.reader
+                    .reader
+                    .seek(SeekFrom::Current(data_size))
+                    .map(|_| header)
+                    .map_err(SegyError::from)
+            }
+            Ok(None) => {
+                self.finished = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        self.finished = result.is_err();
+        self.traces_read += 1;
+        Some(result)
+    }
+}
+
+/// Parses a 400-byte binary header buffer using the given byte order.
+pub(crate) fn parse_binary_header(
+    buffer: &[u8],
+    endianness: Endianness,
+) -> Result<BinaryHeader, SegyError> {
+    let job_id = read_i32(buffer, 0, endianness)?; // bytes 3201-3204 -> offset 0
+    let line_number = read_i32(buffer, 4, endianness)?; // bytes 3205-3208 -> offset 4
+    let reel_number = read_i32(buffer, 8, endianness)?; // bytes 3209-3212 -> offset 8
+    let data_traces_per_ensemble = read_u16(buffer, 12, endianness)?; // bytes 3213-3214
+    let auxiliary_traces_per_ensemble = read_u16(buffer, 14, endianness)?; // bytes 3215-3216
+
+    let sample_interval_us = read_u16(buffer, 16, endianness)?; // bytes 3217-3218 -> offset 16
+    let original_sample_interval_us = read_u16(buffer, 18, endianness)?; // bytes 3219-3220
+    let samples_per_trace = read_u16(buffer, 20, endianness)?; // bytes 3221-3222 -> offset 20
+    let original_samples_per_trace = read_u16(buffer, 22, endianness)?; // bytes 3223-3224
+    let format_code_raw = read_u16(buffer, 24, endianness)?; // bytes 3225-3226 -> offset 24
+    let sample_format_code = SampleFormat::from_code(format_code_raw)?;
+
+    let ensemble_fold = read_u16(buffer, 26, endianness)?; // bytes 3227-3228
+    let trace_sorting_code = read_i16(buffer, 28, endianness)?; // bytes 3229-3230
+    let vertical_sum_code = read_u16(buffer, 30, endianness)?; // bytes 3231-3232
+    let sweep_frequency_start_hz = read_u16(buffer, 32, endianness)?; // bytes 3233-3234
+    let sweep_frequency_end_hz = read_u16(buffer, 34, endianness)?; // bytes 3235-3236
+    let sweep_length_ms = read_u16(buffer, 36, endianness)?; // bytes 3237-3238
+    let sweep_type_code = read_u16(buffer, 38, endianness)?; // bytes 3239-3240
+    let sweep_channel = read_u16(buffer, 40, endianness)?; // bytes 3241-3242
+    let sweep_taper_start_ms = read_u16(buffer, 42, endianness)?; // bytes 3243-3244
+    let sweep_taper_end_ms = read_u16(buffer, 44, endianness)?; // bytes 3245-3246
+    let taper_type = read_u16(buffer, 46, endianness)?; // bytes 3247-3248
+    let correlated_data_traces = read_u16(buffer, 48, endianness)?; // bytes 3249-3250
+    let binary_gain_recovered = read_u16(buffer, 50, endianness)?; // bytes 3251-3252
+    let amplitude_recovery_method = read_u16(buffer, 52, endianness)?; // bytes 3253-3254
+    let measurement_system = read_u16(buffer, 54, endianness)?; // bytes 3255-3256
+    let impulse_signal_polarity = read_u16(buffer, 56, endianness)?; // bytes 3257-3258
+    let vibratory_polarity_code = read_u16(buffer, 58, endianness)?; // bytes 3259-3260
+
+    // SEG-Y rev2 extensions of the 16-bit fields above.
+    let extended_data_traces_per_ensemble = read_u32(buffer, 60, endianness)?; // bytes 3261-3264
+    let extended_auxiliary_traces_per_ensemble = read_u32(buffer, 64, endianness)?; // bytes 3265-3268
+    let extended_samples_per_trace = read_u32(buffer, 68, endianness)?; // bytes 3269-3272
+    let extended_sample_interval = read_f64(buffer, 72, endianness)?; // bytes 3273-3280
+    let extended_original_sample_interval = read_f64(buffer, 80, endianness)?; // bytes 3281-3288
+    let extended_original_samples_per_trace = read_u32(buffer, 88, endianness)?; // bytes 3289-3292
+    let extended_ensemble_fold = read_u32(buffer, 92, endianness)?; // bytes 3293-3296
+    let byte_order_constant = read_u32(buffer, 96, endianness)?; // bytes 3297-3300
+
+    let segy_revision_major = buffer[300]; // byte 3501
+    let segy_revision_minor = buffer[301]; // byte 3502
+    let fixed_length_traces = read_u16(buffer, 302, endianness)?; // bytes 3503-3504
+    let extended_textual_headers = read_i16(buffer, 304, endianness)?; // bytes 3505-3506
+    let max_additional_trace_headers = read_u32(buffer, 306, endianness)?; // bytes 3507-3510
+    let time_basis_code = read_u16(buffer, 310, endianness)?; // bytes 3511-3512
+    let traces_in_file = read_u64(buffer, 312, endianness)?; // bytes 3513-3520
+    let first_trace_offset = read_u64(buffer, 320, endianness)?; // bytes 3521-3528
+    let trailer_stanza_records = read_i32(buffer, 328, endianness)?; // bytes 3529-3532
+
+    Ok(BinaryHeader {
+        job_id,
+        line_number,
+        reel_number,
+        sample_format_code,
+        samples_per_trace,
+        sample_interval_us,
+        data_traces_per_ensemble,
+        auxiliary_traces_per_ensemble,
+        original_sample_interval_us,
+        original_samples_per_trace,
+        ensemble_fold,
+        trace_sorting_code,
+        vertical_sum_code,
+        sweep_frequency_start_hz,
+        sweep_frequency_end_hz,
+        sweep_length_ms,
+        sweep_type_code,
+        sweep_channel,
+        sweep_taper_start_ms,
+        sweep_taper_end_ms,
+        taper_type,
+        correlated_data_traces,
+        binary_gain_recovered,
+        amplitude_recovery_method,
+        measurement_system,
+        impulse_signal_polarity,
+        vibratory_polarity_code,
+        extended_data_traces_per_ensemble,
+        extended_auxiliary_traces_per_ensemble,
+        extended_samples_per_trace,
+        extended_sample_interval,
+        extended_original_sample_interval,
+        extended_original_samples_per_trace,
+        extended_ensemble_fold,
+        byte_order_constant,
+        segy_revision_major,
+        segy_revision_minor,
+        fixed_length_traces,
+        extended_textual_headers,
+        max_additional_trace_headers,
+        time_basis_code,
+        traces_in_file,
+        first_trace_offset,
+        trailer_stanza_records,
+        endianness,
+        raw: buffer.try_into().ok().map(Box::new),
+    })
+}
+
+/// Parses a 240-byte standard trace header buffer into a `TraceHeader`.
+///
+/// The in-line, cross-line and CDP coordinate fields are taken from the
+/// locations given by `mapping`.
+pub(crate) fn parse_trace_header(
+    buffer: &[u8],
+    endianness: Endianness,
+    mapping: &HeaderMapping,
+) -> Result<TraceHeader, SegyError> {
+    let mut header = TraceHeader {
+        trace_sequence_line: read_i32(buffer, 0, endianness)?, // bytes 1-4
+        trace_sequence_file: read_i32(buffer, 4, endianness)?, // bytes 5-8
+        field_record_number: read_i32(buffer, 8, endianness)?, // bytes 9-12
+        trace_number: read_i32(buffer, 12, endianness)?, // bytes 13-16
+        source_point_number: read_i32(buffer, 16, endianness)?, // bytes 17-20
+        ensemble_number: read_i32(buffer, 20, endianness)?, // bytes 21-24
+        trace_in_ensemble: read_i32(buffer, 24, endianness)?, // bytes 25-28
+        trace_identification_code: read_i16(buffer, 28, endianness)?, // bytes 29-30
+        vertically_summed_traces: read_i16(buffer, 30, endianness)?, // bytes 31-32
+        horizontally_stacked_traces: read_i16(buffer, 32, endianness)?, // bytes 33-34
+        data_use: read_i16(buffer, 34, endianness)?, // bytes 35-36
+        offset: read_i32(buffer, 36, endianness)?, // bytes 37-40
+        receiver_group_elevation: read_i32(buffer, 40, endianness)?, // bytes 41-44
+        source_surface_elevation: read_i32(buffer, 44, endianness)?, // bytes 45-48
+        source_depth: read_i32(buffer, 48, endianness)?, // bytes 49-52
+        receiver_datum_elevation: read_i32(buffer, 52, endianness)?, // bytes 53-56
+        source_datum_elevation: read_i32(buffer, 56, endianness)?, // bytes 57-60
+        source_water_depth: read_i32(buffer, 60, endianness)?, // bytes 61-64
+        group_water_depth: read_i32(buffer, 64, endianness)?, // bytes 65-68
+        elevation_scalar: read_i16(buffer, 68, endianness)?, // bytes 69-70
+        coord_scalar: read_i16(buffer, 70, endianness)?, // bytes 71-72
+        source_x: read_i32(buffer, 72, endianness)?, // bytes 73-76
+        source_y: read_i32(buffer, 76, endianness)?, // bytes 77-80
+        group_x: read_i32(buffer, 80, endianness)?, // bytes 81-84
+        group_y: read_i32(buffer, 84, endianness)?, // bytes 85-88
+        coordinate_units: read_i16(buffer, 88, endianness)?, // bytes 89-90
+        weathering_velocity: read_i16(buffer, 90, endianness)?, // bytes 91-92
+        subweathering_velocity: read_i16(buffer, 92, endianness)?, // bytes 93-94
+        source_uphole_time_ms: read_i16(buffer, 94, endianness)?, // bytes 95-96
+        group_uphole_time_ms: read_i16(buffer, 96, endianness)?, // bytes 97-98
+        source_static_correction_ms: read_i16(buffer, 98, endianness)?, // bytes 99-100
+        group_static_correction_ms: read_i16(buffer, 100, endianness)?, // bytes 101-102
+        total_static_applied_ms: read_i16(buffer, 102, endianness)?, // bytes 103-104
+        lag_time_a_ms: read_i16(buffer, 104, endianness)?, // bytes 105-106
+        lag_time_b_ms: read_i16(buffer, 106, endianness)?, // bytes 107-108
+        delay_recording_time_ms: read_i16(buffer, 108, endianness)?, // bytes 109-110
+        mute_time_start_ms: read_i16(buffer, 110, endianness)?, // bytes 111-112
+        mute_time_end_ms: read_i16(buffer, 112, endianness)?, // bytes 113-114
+        trace_sample_count: read_u16(buffer, 114, endianness)?, // bytes 115-116
+        trace_sample_interval_us: read_u16(buffer, 116, endianness)?, // bytes 117-118
+        gain_type: read_i16(buffer, 118, endianness)?, // bytes 119-120
+        instrument_gain_constant_db: read_i16(buffer, 120, endianness)?, // bytes 121-122
+        instrument_early_gain_db: read_i16(buffer, 122, endianness)?, // bytes 123-124
+        correlated: read_i16(buffer, 124, endianness)?, // bytes 125-126
+        sweep_frequency_start_hz: read_i16(buffer, 126, endianness)?, // bytes 127-128
+        sweep_frequency_end_hz: read_i16(buffer, 128, endianness)?, // bytes 129-130
+        sweep_length_ms: read_i16(buffer, 130, endianness)?, // bytes 131-132
+        sweep_type: read_i16(buffer, 132, endianness)?, // bytes 133-134
+        sweep_taper_start_ms: read_i16(buffer, 134, endianness)?, // bytes 135-136
+        sweep_taper_end_ms: read_i16(buffer, 136, endianness)?, // bytes 137-138
+        taper_type: read_i16(buffer, 138, endianness)?, // bytes 139-140
+        alias_filter_frequency_hz: read_i16(buffer, 140, endianness)?, // bytes 141-142
+        alias_filter_slope: read_i16(buffer, 142, endianness)?, // bytes 143-144
+        notch_filter_frequency_hz: read_i16(buffer, 144, endianness)?, // bytes 145-146
+        notch_filter_slope: read_i16(buffer, 146, endianness)?, // bytes 147-148
+        low_cut_frequency_hz: read_i16(buffer, 148, endianness)?, // bytes 149-150
+        high_cut_frequency_hz: read_i16(buffer, 150, endianness)?, // bytes 151-152
+        low_cut_slope: read_i16(buffer, 152, endianness)?, // bytes 153-154
+        high_cut_slope: read_i16(buffer, 154, endianness)?, // bytes 155-156
+        year_data_recorded: read_u16(buffer, 156, endianness)?, // bytes 157-158
+        day_of_year: read_u16(buffer, 158, endianness)?, // bytes 159-160
+        hour_of_day: read_u16(buffer, 160, endianness)?, // bytes 161-162
+        minute_of_hour: read_u16(buffer, 162, endianness)?, // bytes 163-164
+        second_of_minute: read_u16(buffer, 164, endianness)?, // bytes 165-166
+        time_basis_code: read_i16(buffer, 166, endianness)?, // bytes 167-168
+        trace_weighting_factor: read_i16(buffer, 168, endianness)?, // bytes 169-170
+        geophone_group_roll_switch: read_i16(buffer, 170, endianness)?, // bytes 171-172
+        geophone_group_first_trace: read_i16(buffer, 172, endianness)?, // bytes 173-174
+        geophone_group_last_trace: read_i16(buffer, 174, endianness)?, // bytes 175-176
+        gap_size: read_i16(buffer, 176, endianness)?, // bytes 177-178
+        over_travel: read_i16(buffer, 178, endianness)?, // bytes 179-180
+        cdp_x: read_i32(buffer, 180, endianness)?, // bytes 181-184
+        cdp_y: read_i32(buffer, 184, endianness)?, // bytes 185-188
+        inline_number: read_i32(buffer, 188, endianness)?, // bytes 189-192
+        crossline_number: read_i32(buffer, 192, endianness)?, // bytes 193-196
+        shotpoint_number: read_i32(buffer, 196, endianness)?, // bytes 197-200
+        shotpoint_scalar: read_i16(buffer, 200, endianness)?, // bytes 201-202
+        trace_value_measurement_unit: read_i16(buffer, 202, endianness)?, // bytes 203-204
+        transduction_constant_mantissa: read_i32(buffer, 204, endianness)?, // bytes 205-208
+        transduction_constant_exponent: read_i16(buffer, 208, endianness)?, // bytes 209-210
+        transduction_units: read_i16(buffer, 210, endianness)?, // bytes 211-212
+        device_identifier: read_i16(buffer, 212, endianness)?, // bytes 213-214
+        time_scalar: read_i16(buffer, 214, endianness)?, // bytes 215-216
+        source_type_orientation: read_i16(buffer, 216, endianness)?, // bytes 217-218
+        source_energy_direction_mantissa: read_i32(buffer, 218, endianness)?, // bytes 219-222
+        source_energy_direction_exponent: read_i16(buffer, 222, endianness)?, // bytes 223-224
+        source_measurement_mantissa: read_i32(buffer, 224, endianness)?, // bytes 225-228
+        source_measurement_exponent: read_i16(buffer, 228, endianness)?, // bytes 229-230
+        source_measurement_unit: read_i16(buffer, 230, endianness)?, // bytes 231-232
+        raw: None,
+    };
+    mapping.apply(buffer, endianness, &mut header)?;
+    Ok(header)
+}
+
+/// Parses a 240-byte SEG-Y rev2 trace header extension 1 buffer.
+pub(crate) fn parse_trace_header_extension(
+    buffer: &[u8],
+    endianness: Endianness,
+) -> Result<TraceHeaderExtension1, SegyError> {
+    Ok(TraceHeaderExtension1 {
+        trace_sequence_line: read_u64(buffer, 0, endianness)?, // bytes 1-8
+        trace_sequence_file: read_u64(buffer, 8, endianness)?, // bytes 9-16
+        field_record_number: read_i64(buffer, 16, endianness)?, // bytes 17-24
+        ensemble_number: read_i64(buffer, 24, endianness)?, // bytes 25-32
+        receiver_group_elevation: read_f64(buffer, 32, endianness)?, // bytes 33-40
+        receiver_group_depth: read_f64(buffer, 40, endianness)?, // bytes 41-48
+        source_surface_elevation: read_f64(buffer, 48, endianness)?, // bytes 49-56
+        source_depth: read_f64(buffer, 56, endianness)?, // bytes 57-64
+        receiver_datum_elevation: read_f64(buffer, 64, endianness)?, // bytes 65-72
+        source_datum_elevation: read_f64(buffer, 72, endianness)?, // bytes 73-80
+        source_water_depth: read_f64(buffer, 80, endianness)?, // bytes 81-88
+        group_water_depth: read_f64(buffer, 88, endianness)?, // bytes 89-96
+        source_x: read_f64(buffer, 96, endianness)?, // bytes 97-104
+        source_y: read_f64(buffer, 104, endianness)?, // bytes 105-112
+        group_x: read_f64(buffer, 112, endianness)?, // bytes 113-120
+        group_y: read_f64(buffer, 120, endianness)?, // bytes 121-128
+        offset: read_f64(buffer, 128, endianness)?, // bytes 129-136
+        trace_sample_count: read_u32(buffer, 136, endianness)?, // bytes 137-140
+        nanoseconds: read_i32(buffer, 140, endianness)?, // bytes 141-144
+        sample_interval: read_f64(buffer, 144, endianness)?, // bytes 145-152
+        cable_number: read_i32(buffer, 152, endianness)?, // bytes 153-156
+        additional_trace_headers: read_u16(buffer, 156, endianness)?, // bytes 157-158
+        last_trace_flag: read_i16(buffer, 158, endianness)?, // bytes 159-160
+        cdp_x: read_f64(buffer, 160, endianness)?, // bytes 161-168
+        cdp_y: read_f64(buffer, 168, endianness)?, // bytes 169-176
+    })
+}
+
+/// Decodes a 3200-byte textual header record, converting from EBCDIC when it
+/// does not already look like ASCII.
+pub(crate) fn decode_textual_header(buffer: &[u8]) -> EbcdicHeader {
+    // 1. Prepare a second buffer for the ASCII output if needed.
+    let mut ascii_buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+
+    // 2. Decide if it's likely ASCII or EBCDIC.
+    //    If it's ASCII, we just copy the buffer; otherwise, we convert.
+    if is_probably_ascii(buffer) {
+        // Likely ASCII: copy the buffer directly.
+        ascii_buffer.copy_from_slice(buffer);
+    } else {
+        // Likely EBCDIC: convert to ASCII.
+        Ebcdic::ebcdic_to_ascii(
+            buffer,              // src
+            &mut ascii_buffer,   // dest
+            EBCDIC_HEADER_SIZE,  // number of bytes to convert
+            true,                // non_printable_to_space
+            true,                // nel_to_lf
+        );
+    }
+
+    // 3. Convert that ASCII buffer into a Rust String (lossy to avoid errors
+    //    with unexpected byte values).
+    let text = String::from_utf8_lossy(&ascii_buffer).into_owned();
+
+    // 4. Return the EbcdicHeader struct with the final text.
+    EbcdicHeader {
+        text,
+        raw: buffer.to_vec(),
+    }
+}
+
+fn is_probably_ascii(buffer: &[u8]) -> bool {
+    let mut ascii_count = 0;
+    let total = buffer.len();
+
+    for &b in buffer {
+        // Check if b is a standard ASCII printable character (0x20..=0x7E),
+        // or a few allowed control chars like \n (0x0A), \r (0x0D), tab (0x09).
+        if (0x20..=0x7E).contains(&b) || b == b'\n' || b == b'\r' || b == b'\t' {
+            ascii_count += 1;
+        }
+    }
+
+    // The EBCDIC space (0x40) is a printable ASCII '@', so a mostly blank EBCDIC
+    // header also looks like ASCII; tell them apart by which space is more common.
+    let ascii_spaces = buffer.iter().filter(|&&b| b == b' ').count();
+    let ebcdic_spaces = buffer.iter().filter(|&&b| b == 0x40).count();
+
+    // If more than 80% of the characters appear to be in the valid ASCII range,
+    // we assume it is ASCII. Adjust threshold as needed.
+    let ratio = ascii_count as f32 / total as f32;
+    ratio > 0.80 && ascii_spaces >= ebcdic_spaces
+}
+
+/// Collects the extended textual headers that follow the binary header, one
+/// 3200-byte record at a time, for the readers that fetch them in different ways.
+pub(crate) struct ExtendedTextualHeaders {
+    /// Bytes 3505-3506 of the binary header; -1 means the records end with the
+    /// one holding a `((SEG: EndText))` stanza.
+    count: i16,
+    headers: Vec<EbcdicHeader>,
+}
+
+impl ExtendedTextualHeaders {
+    /// Starts collecting the headers announced by bytes 3505-3506 of the binary header.
+    pub(crate) fn new(count: i16) -> Result<Self, SegyError> {
+        if count < -1 {
+            return Err(SegyError::ParseError(format!(
+                "Invalid number of extended textual headers: {}",
+                count
+            )));
+        }
+        Ok(ExtendedTextualHeaders {
+            count,
+            headers: Vec::new(),
+        })
+    }
+
+    /// Returns true once every header has been pushed.
+    pub(crate) fn is_complete(&self) -> bool {
+        match self.count {
+            -1 => self
+                .headers
+                .last()
+                .is_some_and(|header| contains_end_text(&header.text)),
+            count => self.headers.len() >= count as usize,
+        }
+    }
+
+    /// Decodes the next raw 3200-byte record.
+    pub(crate) fn push(&mut self, record: &[u8]) {
+        self.headers.push(decode_textual_header(record));
+    }
+
+    pub(crate) fn into_headers(self) -> Vec<EbcdicHeader> {
+        self.headers
+    }
+}
+
+/// Returns true if the text contains the `((SEG: EndText))` stanza that closes
+/// a variable-length set of extended textual headers.
+pub(crate) fn contains_end_text(text: &str) -> bool {
+    let normalized: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    normalized.contains("((SEG:ENDTEXT))")
+}
+
+/// Detects the byte order of a file from its 400-byte binary header.
+///
+/// SEG-Y rev2 files carry the constant 0x01020304 in bytes 3297-3300, which reads
+/// back as 0x04030201 when the file is little-endian. Older files lack it, so we
+/// fall back to checking which byte order yields a valid sample format code and
+/// a plausible number of samples per trace, preferring big-endian on a tie.
+pub(crate) fn detect_endianness(buffer: &[u8]) -> Endianness {
+    match buffer.get(96..100) {
+        Some([1, 2, 3, 4]) => return Endianness::Big,
+        Some([4, 3, 2, 1]) => return Endianness::Little,
+        _ => {}
+    }
+
+    let score = |endianness: Endianness| {
+        let format_code = read_u16(buffer, 24, endianness).unwrap_or(0);
+        let samples_per_trace = read_u16(buffer, 20, endianness).unwrap_or(0);
+        let mut score = 0;
+        if SampleFormat::from_code(format_code).is_ok() {
+            score += 2;
+        }
+        // Real files rarely exceed a few tens of thousands of samples, while a
+        // byte-swapped small count lands in the upper range (e.g. 1000 -> 59395).
+        if samples_per_trace > 0 && samples_per_trace < 0x8000 {
+            score += 1;
+        }
+        score
+    };
+
+    if score(Endianness::Little) > score(Endianness::Big) {
+        Endianness::Little
+    } else {
+        Endianness::Big
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        samples::TraceSamples,
+        testing::{junk_rev1_file, sample_traces, write_file},
+        writer::{encode_textual_header, SegyWriter, TextEncoding},
+    };
+
+    #[test]
+    fn rev1_ignores_junk_in_rev2_fields() {
+        let traces = sample_traces(5, 4);
+        let mut reader = SegyReader::open(Cursor::new(junk_rev1_file(&traces))).unwrap();
+
+        assert_eq!(reader.data_offset().unwrap(), 3600);
+        assert_eq!(reader.trace_count().unwrap(), 5);
+        assert_eq!(reader.scan_headers().unwrap().len(), 5);
+        assert_eq!(reader.build_trace_offsets().unwrap().len(), 5);
+        assert_eq!(reader.trace(4).unwrap().data_samples, traces[4].data_samples);
+        assert!(reader.read_trailer_records().unwrap().is_empty());
+
+        reader.get_mut().seek(SeekFrom::Start(3600)).unwrap();
+        let streamed: Vec<Trace> = reader.traces().unwrap().collect::<Result<_, _>>().unwrap();
+        assert_eq!(streamed.len(), 5);
+        assert_eq!(streamed[2].data_samples, traces[2].data_samples);
+    }
+
+    #[test]
+    fn trace_headers_keep_raw_bytes_only_when_asked() {
+        let traces = sample_traces(3, 4);
+        let bytes = write_file(SampleFormat::IeeeFloat, &traces);
+        let mut reader = SegyReader::open(Cursor::new(bytes.clone())).unwrap();
+
+        let header = reader.trace_header(1).unwrap();
+        assert!(header.raw.is_none());
+        assert_eq!(header, traces[1].header);
+
+        reader.set_keep_raw_trace_headers(true);
+        let header = reader.trace_header(1).unwrap();
+        let start = 3600 + 256;
+        assert_eq!(header.raw.as_deref().unwrap()[..], bytes[start..start + 240]);
+        // The raw bytes are not part of equality.
+        assert_eq!(header, traces[1].header);
+        assert_eq!(reader.trace(2).unwrap(), traces[2]);
+    }
+
+    #[test]
+    fn typed_traces_keep_integer_samples_exact() {
+        for (sample_format, samples, first) in [
+            (
+                SampleFormat::Int32,
+                TraceSamples::I32(vec![16_777_217, -16_777_219, i32::MAX]),
+                16_777_217i128,
+            ),
+            (
+                SampleFormat::UInt64,
+                TraceSamples::U64(vec![(1 << 53) + 1, u64::MAX, 3]),
+                (1i128 << 53) + 1,
+            ),
+        ] {
+            let trace = TypedTrace {
+                header: TraceHeader {
+                    trace_sample_count: 3,
+                    ..Default::default()
+                },
+                samples: samples.clone(),
+            };
+            let mut writer = SegyWriter::new(Cursor::new(Vec::new()));
+            writer.write_textual_header("C 1 TYPED").unwrap();
+            writer
+                .write_binary_header(&BinaryHeader::new(sample_format, 3, 4000))
+                .unwrap();
+            writer.write_typed_trace(&trace).unwrap();
+            writer.write_typed_trace(&trace).unwrap();
+            let bytes = writer.finish().unwrap().into_inner();
+
+            let mut reader = SegyReader::open(Cursor::new(bytes)).unwrap();
+            assert_eq!(reader.typed_trace(1).unwrap().samples, samples);
+            // As f32 the same samples lose their low bits.
+            let lossy = reader.trace(1).unwrap().data_samples;
+            assert_eq!(lossy, samples.to_f32());
+            assert_eq!(lossy[0] as i128, first - 1);
+        }
+    }
+
+    #[test]
+    fn rev2_binary_header_fields_are_read_from_their_bytes() {
+        let mut buffer = vec![0u8; BINARY_HEADER_SIZE];
+        buffer[24..26].copy_from_slice(&5u16.to_be_bytes()); // bytes 3225-3226
+        buffer[300] = 2; // byte 3501
+        buffer[301] = 1; // byte 3502
+        buffer[302..304].copy_from_slice(&1u16.to_be_bytes()); // bytes 3503-3504
+        buffer[304..306].copy_from_slice(&(-1i16).to_be_bytes()); // bytes 3505-3506
+        buffer[306..310].copy_from_slice(&3u32.to_be_bytes()); // bytes 3507-3510
+        buffer[310..312].copy_from_slice(&4u16.to_be_bytes()); // bytes 3511-3512
+        buffer[312..320].copy_from_slice(&0x0102_0304_0506_0708u64.to_be_bytes()); // bytes 3513-3520
+        buffer[320..328].copy_from_slice(&0x1112_1314_1516_1718u64.to_be_bytes()); // bytes 3521-3528
+        buffer[328..332].copy_from_slice(&7i32.to_be_bytes()); // bytes 3529-3532
+
+        let binary_header = parse_binary_header(&buffer, Endianness::Big).unwrap();
+        assert_eq!(binary_header.segy_revision_major, 2);
+        assert_eq!(binary_header.segy_revision_minor, 1);
+        assert_eq!(binary_header.fixed_length_traces, 1);
+        assert_eq!(binary_header.extended_textual_headers, -1);
+        assert_eq!(binary_header.max_additional_trace_headers, 3);
+        assert_eq!(binary_header.time_basis_code, 4);
+        assert_eq!(binary_header.traces_in_file, 0x0102_0304_0506_0708);
+        assert_eq!(binary_header.first_trace_offset, 0x1112_1314_1516_1718);
+        assert_eq!(binary_header.trailer_stanza_records, 7);
+    }
+
+    /// A binary header with the sample format code (bytes 3225-3226) and samples
+    /// per trace (bytes 3221-3222) given as raw bytes.
+    fn binary_header_bytes(format_code: [u8; 2], samples_per_trace: [u8; 2]) -> Vec<u8> {
+        let mut buffer = vec![0u8; BINARY_HEADER_SIZE];
+        buffer[20..22].copy_from_slice(&samples_per_trace);
+        buffer[24..26].copy_from_slice(&format_code);
+        buffer
+    }
+
+    #[test]
+    fn byte_order_constant_decides_the_endianness() {
+        // The fields alone look big-endian, but the constant reads back swapped.
+        let mut buffer = binary_header_bytes(5u16.to_be_bytes(), 1000u16.to_be_bytes());
+        buffer[96..100].copy_from_slice(&0x0102_0304u32.to_le_bytes()); // bytes 3297-3300
+        assert_eq!(detect_endianness(&buffer), Endianness::Little);
+
+        let mut buffer = binary_header_bytes(5u16.to_le_bytes(), 1000u16.to_le_bytes());
+        buffer[96..100].copy_from_slice(&0x0102_0304u32.to_be_bytes());
+        assert_eq!(detect_endianness(&buffer), Endianness::Big);
+    }
+
+    #[test]
+    fn endianness_falls_back_to_plausible_fields() {
+        let buffer = binary_header_bytes(5u16.to_le_bytes(), 1000u16.to_le_bytes());
+        assert_eq!(detect_endianness(&buffer), Endianness::Little);
+        let buffer = binary_header_bytes(5u16.to_be_bytes(), 1000u16.to_be_bytes());
+        assert_eq!(detect_endianness(&buffer), Endianness::Big);
+
+        // A valid format code outweighs a plausible sample count: 32768 samples
+        // little-endian, 128 big-endian.
+        let buffer = binary_header_bytes(1u16.to_le_bytes(), [0x00, 0x80]);
+        assert_eq!(detect_endianness(&buffer), Endianness::Little);
+
+        // Both byte orders score the same, so big-endian wins.
+        let buffer = binary_header_bytes([0, 0], [1, 1]);
+        assert_eq!(detect_endianness(&buffer), Endianness::Big);
+        assert_eq!(detect_endianness(&[0u8; BINARY_HEADER_SIZE]), Endianness::Big);
+    }
+
+    #[test]
+    fn blank_ebcdic_headers_are_not_mistaken_for_ascii() {
+        let ascii = encode_textual_header("C 1 CLIENT", TextEncoding::Ascii).unwrap();
+        assert!(is_probably_ascii(&ascii));
+        let ebcdic = encode_textual_header("C 1 CLIENT", TextEncoding::Ebcdic).unwrap();
+        assert!(!is_probably_ascii(&ebcdic));
+
+        // EBCDIC spaces are all printable ASCII '@', so only the spaces tell them apart.
+        let mut blank = vec![0x40u8; EBCDIC_HEADER_SIZE];
+        assert!(!is_probably_ascii(&blank));
+        blank[..1601].fill(b' ');
+        assert!(is_probably_ascii(&blank));
+        assert_eq!(decode_textual_header(&ebcdic).text.trim_end(), "C 1 CLIENT");
     }
 }
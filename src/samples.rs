@@ -0,0 +1,403 @@
+use std::borrow::Cow;
+
+use crate::{
+    errors::SegyError,
+    format::{Endianness, SampleFormat},
+    utils::{
+        decode_ibm_slice, encode_ibm_slice, read_f64, read_i16, read_i24, read_i32, read_i64,
+        read_u16, read_u24, read_u32, read_u64, IbmFloatPolicy, IbmNonFinite,
+    },
+};
+
+/// Trace samples stored in the native type of their SEG-Y sample format.
+///
+/// Unlike `Trace::data_samples`, no precision is lost: integer formats keep their
+/// exact values and 64-bit formats keep all of their bits.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceSamples {
+    /// IBM float (1), fixed point with gain (4) and IEEE 32-bit float (5) samples.
+    F32(Vec<f32>),
+    /// IEEE 64-bit float samples (6).
+    F64(Vec<f64>),
+    /// 8-bit integer samples (8).
+    I8(Vec<i8>),
+    /// 16-bit integer samples (3).
+    I16(Vec<i16>),
+    /// 32-bit (2) and 24-bit (7) integer samples.
+    I32(Vec<i32>),
+    /// 64-bit integer samples (9).
+    I64(Vec<i64>),
+    /// 8-bit unsigned integer samples (16).
+    U8(Vec<u8>),
+    /// 16-bit unsigned integer samples (11).
+    U16(Vec<u16>),
+    /// 32-bit (10) and 24-bit (15) unsigned integer samples.
+    U32(Vec<u32>),
+    /// 64-bit unsigned integer samples (12).
+    U64(Vec<u64>),
+}
+
+impl TraceSamples {
+    /// Decodes raw trace sample bytes in the given format and byte order.
+    pub fn from_bytes(
+        buffer: &[u8],
+        sample_format: SampleFormat,
+        endianness: Endianness,
+    ) -> Result<Self, SegyError> {
+        Self::from_bytes_with_policy(buffer, sample_format, endianness, IbmFloatPolicy::default())
+    }
+
+    /// Decodes raw trace sample bytes, converting IBM floats outside the `f32`
+    /// range according to `ibm_policy`.
+    pub fn from_bytes_with_policy(
+        buffer: &[u8],
+        sample_format: SampleFormat,
+        endianness: Endianness,
+        ibm_policy: IbmFloatPolicy,
+    ) -> Result<Self, SegyError> {
+        let size = sample_format.sample_size();
+        let chunks = buffer.chunks_exact(size);
+        let samples = match sample_format {
+            SampleFormat::IbmFloat => {
+                let mut values = vec![0.0; chunks.len()];
+                decode_ibm_slice(
+                    &buffer[..values.len() * size],
+                    &mut values,
+                    endianness,
+                    ibm_policy,
+                )?;
+                TraceSamples::F32(values)
+            }
+            SampleFormat::Int32 => TraceSamples::I32(
+                chunks
+                    .map(|chunk| read_i32(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::Int16 => TraceSamples::I16(
+                chunks
+                    .map(|chunk| read_i16(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::FixedPointWithGain => {
+                // Byte 1 is zero, byte 2 the gain exponent and bytes 3-4 a two's
+                // complement mantissa; the value is mantissa * 2^-gain.
+                let mut values = Vec::with_capacity(chunks.len());
+                for chunk in chunks {
+                    let word = read_u32(chunk, 0, endianness)?;
+                    let gain = ((word >> 16) & 0xFF) as i32;
+                    let mantissa = (word & 0xFFFF) as u16 as i16;
+                    values.push(mantissa as f32 * 2f32.powi(-gain));
+                }
+                TraceSamples::F32(values)
+            }
+            SampleFormat::IeeeFloat => TraceSamples::F32(
+                chunks
+                    .map(|chunk| read_u32(chunk, 0, endianness).map(f32::from_bits))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::IeeeDouble => TraceSamples::F64(
+                chunks
+                    .map(|chunk| read_f64(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::Int24 => TraceSamples::I32(
+                chunks
+                    .map(|chunk| read_i24(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::Int8 => TraceSamples::I8(chunks.map(|chunk| chunk[0] as i8).collect()),
+            SampleFormat::Int64 => TraceSamples::I64(
+                chunks
+                    .map(|chunk| read_i64(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::UInt32 => TraceSamples::U32(
+                chunks
+                    .map(|chunk| read_u32(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::UInt16 => TraceSamples::U16(
+                chunks
+                    .map(|chunk| read_u16(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::UInt64 => TraceSamples::U64(
+                chunks
+                    .map(|chunk| read_u64(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::UInt24 => TraceSamples::U32(
+                chunks
+                    .map(|chunk| read_u24(chunk, 0, endianness))
+                    .collect::<Result<_, _>>()?,
+            ),
+            SampleFormat::UInt8 => TraceSamples::U8(buffer.to_vec()),
+        };
+        Ok(samples)
+    }
+
+    /// Returns the number of samples.
+    pub fn len(&self) -> usize {
+        match self {
+            TraceSamples::F32(values) => values.len(),
+            TraceSamples::F64(values) => values.len(),
+            TraceSamples::I8(values) => values.len(),
+            TraceSamples::I16(values) => values.len(),
+            TraceSamples::I32(values) => values.len(),
+            TraceSamples::I64(values) => values.len(),
+            TraceSamples::U8(values) => values.len(),
+            TraceSamples::U16(values) => values.len(),
+            TraceSamples::U32(values) => values.len(),
+            TraceSamples::U64(values) => values.len(),
+        }
+    }
+
+    /// Returns true if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Converts the samples to `f32`, rounding values that `f32` cannot represent exactly.
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self {
+            TraceSamples::F32(values) => values.clone(),
+            TraceSamples::F64(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::I8(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::I16(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::I32(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::I64(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::U8(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::U16(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::U32(values) => values.iter().map(|&v| v as f32).collect(),
+            TraceSamples::U64(values) => values.iter().map(|&v| v as f32).collect(),
+        }
+    }
+
+    /// Converts the samples to `f32`, reusing the allocation when they already are.
+    pub fn into_f32(self) -> Vec<f32> {
+        match self {
+            TraceSamples::F32(values) => values,
+            other => other.to_f32(),
+        }
+    }
+
+    /// Converts the samples to `f64`.
+    ///
+    /// This is exact for every format except 64-bit integers beyond 2^53.
+    pub fn to_f64(&self) -> Vec<f64> {
+        match self {
+            TraceSamples::F32(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::F64(values) => values.clone(),
+            TraceSamples::I8(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::I16(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::I32(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::I64(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::U8(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::U16(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::U32(values) => values.iter().map(|&v| v as f64).collect(),
+            TraceSamples::U64(values) => values.iter().map(|&v| v as f64).collect(),
+        }
+    }
+
+    /// Encodes the samples in the given format and byte order, the inverse of
+    /// `from_bytes`.
+    ///
+    /// Samples are converted when their type differs from the format: floats
+    /// are rounded to the nearest integer and integers are clamped to the range
+    /// of the format.
+    pub fn to_bytes(
+        &self,
+        sample_format: SampleFormat,
+        endianness: Endianness,
+    ) -> Result<Vec<u8>, SegyError> {
+        self.to_bytes_with_policy(sample_format, endianness, IbmNonFinite::default())
+    }
+
+    /// Encodes the samples, handling NaN and infinities in IBM float output
+    /// according to `non_finite`.
+    pub fn to_bytes_with_policy(
+        &self,
+        sample_format: SampleFormat,
+        endianness: Endianness,
+        non_finite: IbmNonFinite,
+    ) -> Result<Vec<u8>, SegyError> {
+        let mut output = Vec::with_capacity(self.len() * sample_format.sample_size());
+        match sample_format {
+            SampleFormat::IbmFloat => {
+                let values = self.as_f32();
+                output.resize(values.len() * 4, 0);
+                encode_ibm_slice(&values, &mut output, endianness, non_finite)?;
+            }
+            SampleFormat::FixedPointWithGain => {
+                for &value in self.as_f32().iter() {
+                    let (gain, mantissa) = fixed_point_with_gain(value);
+                    let word = (gain as u32) << 16 | mantissa as u16 as u32;
+                    push_bytes(&mut output, word.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::IeeeFloat => {
+                for value in self.as_f32().iter() {
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::IeeeDouble => {
+                for value in self.to_f64() {
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::Int8 => {
+                for value in self.to_integers() {
+                    output.push(value.clamp(i8::MIN as i128, i8::MAX as i128) as i8 as u8);
+                }
+            }
+            SampleFormat::Int16 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(i16::MIN as i128, i16::MAX as i128) as i16;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::Int24 => {
+                for value in self.to_integers() {
+                    let bytes = (value.clamp(-(1 << 23), (1 << 23) - 1) as i32).to_be_bytes();
+                    push_bytes(&mut output, [bytes[1], bytes[2], bytes[3]], endianness);
+                }
+            }
+            SampleFormat::Int32 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(i32::MIN as i128, i32::MAX as i128) as i32;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::Int64 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::UInt8 => {
+                for value in self.to_integers() {
+                    output.push(value.clamp(0, u8::MAX as i128) as u8);
+                }
+            }
+            SampleFormat::UInt16 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(0, u16::MAX as i128) as u16;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::UInt24 => {
+                for value in self.to_integers() {
+                    let bytes = (value.clamp(0, (1 << 24) - 1) as u32).to_be_bytes();
+                    push_bytes(&mut output, [bytes[1], bytes[2], bytes[3]], endianness);
+                }
+            }
+            SampleFormat::UInt32 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(0, u32::MAX as i128) as u32;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+            SampleFormat::UInt64 => {
+                for value in self.to_integers() {
+                    let value = value.clamp(0, u64::MAX as i128) as u64;
+                    push_bytes(&mut output, value.to_be_bytes(), endianness);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Counts the samples that `to_bytes` changes when encoding them in an integer
+    /// format, returned as `(clipped, rounded)`.
+    ///
+    /// Clipped samples are outside the range of the format and clamped to it; NaN,
+    /// written as zero, also counts as clipped. Rounded samples are floats with a
+    /// fractional part. For the fixed point format, samples beyond the range of
+    /// its 16-bit mantissa count as clipped; nothing is counted for the floating
+    /// point formats.
+    pub fn integer_losses(&self, sample_format: SampleFormat) -> (usize, usize) {
+        if sample_format == SampleFormat::FixedPointWithGain {
+            // Samples are encoded as `f32`, see `to_bytes`.
+            let values = self.as_f32();
+            let clipped = values.iter().filter(|&&value| {
+                let nearest = (value as f64).round();
+                value.is_nan() || !(i16::MIN as f64..=i16::MAX as f64).contains(&nearest)
+            });
+            return (clipped.count(), 0);
+        }
+        let Some((min, max)) = sample_format.integer_range() else {
+            return (0, 0);
+        };
+        let (mut clipped, mut rounded) = (0, 0);
+        match self {
+            TraceSamples::F32(_) | TraceSamples::F64(_) => {
+                for value in self.to_f64() {
+                    let nearest = value.round();
+                    if value.is_nan() || !(min..=max).contains(&(nearest as i128)) {
+                        clipped += 1;
+                    } else if nearest != value {
+                        rounded += 1;
+                    }
+                }
+            }
+            _ => {
+                clipped = self
+                    .to_integers()
+                    .into_iter()
+                    .filter(|value| !(min..=max).contains(value))
+                    .count();
+            }
+        }
+        (clipped, rounded)
+    }
+
+    /// Borrows `f32` samples, converting samples of other types.
+    fn as_f32(&self) -> Cow<'_, [f32]> {
+        match self {
+            TraceSamples::F32(values) => Cow::Borrowed(values),
+            other => Cow::Owned(other.to_f32()),
+        }
+    }
+
+    /// Converts the samples to integers wide enough for every integer format,
+    /// rounding floats to the nearest integer (NaN becomes zero).
+    fn to_integers(&self) -> Vec<i128> {
+        match self {
+            TraceSamples::F32(values) => values.iter().map(|&v| v.round() as i128).collect(),
+            TraceSamples::F64(values) => values.iter().map(|&v| v.round() as i128).collect(),
+            TraceSamples::I8(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::I16(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::I32(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::I64(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::U8(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::U16(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::U32(values) => values.iter().map(|&v| v as i128).collect(),
+            TraceSamples::U64(values) => values.iter().map(|&v| v as i128).collect(),
+        }
+    }
+}
+
+/// Appends big-endian `bytes` to `output` in the given byte order.
+fn push_bytes<const N: usize>(output: &mut Vec<u8>, mut bytes: [u8; N], endianness: Endianness) {
+    if endianness == Endianness::Little {
+        bytes.reverse();
+    }
+    output.extend_from_slice(&bytes);
+}
+
+/// Splits a value into the gain exponent and 16-bit mantissa of format 4,
+/// using the largest gain that keeps the mantissa in range.
+fn fixed_point_with_gain(value: f32) -> (u8, i16) {
+    if value == 0.0 || value.is_nan() {
+        return (0, 0);
+    }
+    let value = value as f64;
+    let exponent = value.abs().log2().floor() as i32;
+    let mut gain = (14 - exponent).clamp(0, 255);
+    while gain > 0 && (value * 2f64.powi(gain)).round().abs() > i16::MAX as f64 {
+        gain -= 1;
+    }
+    let mantissa = (value * 2f64.powi(gain)).round();
+    let mantissa = mantissa.clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    (gain as u8, mantissa)
+}
@@ -0,0 +1,172 @@
+use crate::ebcdic_syg::TraceHeader;
+
+/// Trace header values of a whole file, stored column by column.
+///
+/// Only the fields needed for geometry QC are kept, so the table stays small
+/// even for files with millions of traces. Row `i` describes the `i`-th trace.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderTable {
+    /// Byte offset of each trace header in the file.
+    pub trace_offsets: Vec<u64>,
+    /// Trace sequence number within line (bytes 1-4).
+    pub trace_sequence_line: Vec<i32>,
+    /// Trace sequence number within SEG-Y file (bytes 5-8).
+    pub trace_sequence_file: Vec<i32>,
+    /// Original field record number (bytes 9-12).
+    pub field_record_number: Vec<i32>,
+    /// Trace number within original field record (bytes 13-16).
+    pub trace_number: Vec<i32>,
+    /// Energy source point number (bytes 17-20).
+    pub source_point_number: Vec<i32>,
+    /// Ensemble (or "CDP") number (bytes 21-24).
+    pub ensemble_number: Vec<i32>,
+    /// Trace number within the ensemble (bytes 25-28).
+    pub trace_in_ensemble: Vec<i32>,
+    /// Trace identification code (bytes 29-30).
+    pub trace_identification_code: Vec<i16>,
+    /// Distance from source point to receiver group (bytes 37-40).
+    pub offset: Vec<i32>,
+    /// Scalar applied to elevations and depths (bytes 69-70).
+    pub elevation_scalar: Vec<i16>,
+    /// Coordinate scalar (bytes 71-72).
+    pub coord_scalar: Vec<i16>,
+    /// Source coordinate X (bytes 73-76).
+    pub source_x: Vec<i32>,
+    /// Source coordinate Y (bytes 77-80).
+    pub source_y: Vec<i32>,
+    /// Receiver group coordinate X (bytes 81-84).
+    pub group_x: Vec<i32>,
+    /// Receiver group coordinate Y (bytes 85-88).
+    pub group_y: Vec<i32>,
+    /// Number of samples in the trace, with the binary header default applied.
+    pub sample_count: Vec<u16>,
+    /// X coordinate of ensemble (CDP) position, as located by the header mapping.
+    pub cdp_x: Vec<i32>,
+    /// Y coordinate of ensemble (CDP) position, as located by the header mapping.
+    pub cdp_y: Vec<i32>,
+    /// In-line number, as located by the header mapping.
+    pub inline_number: Vec<i32>,
+    /// Cross-line number, as located by the header mapping.
+    pub crossline_number: Vec<i32>,
+    /// Shotpoint number (bytes 197-200).
+    pub shotpoint_number: Vec<i32>,
+}
+
+impl HeaderTable {
+    /// Returns the number of traces in the table.
+    pub fn len(&self) -> usize {
+        self.trace_offsets.len()
+    }
+
+    /// Returns true if the table holds no traces.
+    pub fn is_empty(&self) -> bool {
+        self.trace_offsets.is_empty()
+    }
+
+    /// Appends the values of one trace header found at `trace_offset`.
+    ///
+    /// `sample_count` is the trace's number of samples after applying the
+    /// binary header default.
+    pub fn push(&mut self, trace_offset: u64, header: &TraceHeader, sample_count: u16) {
+        self.trace_offsets.push(trace_offset);
+        self.trace_sequence_line.push(header.trace_sequence_line);
+        self.trace_sequence_file.push(header.trace_sequence_file);
+        self.field_record_number.push(header.field_record_number);
+        self.trace_number.push(header.trace_number);
+        self.source_point_number.push(header.source_point_number);
+        self.ensemble_number.push(header.ensemble_number);
+        self.trace_in_ensemble.push(header.trace_in_ensemble);
+        self.trace_identification_code
+            .push(header.trace_identification_code);
+        self.offset.push(header.offset);
+        self.elevation_scalar.push(header.elevation_scalar);
+        self.coord_scalar.push(header.coord_scalar);
+        self.source_x.push(header.source_x);
+        self.source_y.push(header.source_y);
+        self.group_x.push(header.group_x);
+        self.group_y.push(header.group_y);
+        self.sample_count.push(sample_count);
+        self.cdp_x.push(header.cdp_x);
+        self.cdp_y.push(header.cdp_y);
+        self.inline_number.push(header.inline_number);
+        self.crossline_number.push(header.crossline_number);
+        self.shotpoint_number.push(header.shotpoint_number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Result, Seek, SeekFrom};
+
+    use crate::{
+        format::SampleFormat,
+        mmap::MmapSegyReader,
+        reader::SegyReader,
+        testing::{sample_traces, temp_file, write_file},
+    };
+
+    /// Counts the bytes read through it.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.bytes_read += read;
+            Ok(read)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn scanned_columns_match_the_trace_headers() {
+        let mut traces = sample_traces(5, 8);
+        traces[1].data_samples.truncate(4);
+        traces[1].header.trace_sample_count = 4;
+        traces[3].data_samples.extend([1.0; 8]);
+        traces[3].header.trace_sample_count = 16;
+        // A count of zero means the binary header's 8 samples.
+        traces[4].header.trace_sample_count = 0;
+        for (index, trace) in traces.iter_mut().enumerate() {
+            trace.header.cdp_x = 600_000 + index as i32 * 25;
+            trace.header.cdp_y = 7_000_000 - index as i32 * 25;
+            trace.header.coord_scalar = -(index as i16 + 1);
+        }
+        let bytes = write_file(SampleFormat::IeeeFloat, &traces);
+
+        let mut reader = SegyReader::open(CountingReader {
+            inner: Cursor::new(bytes.clone()),
+            bytes_read: 0,
+        })
+        .unwrap();
+        let bytes_read = reader.get_mut().bytes_read;
+        let table = reader.scan_headers().unwrap();
+        // Only the 240-byte trace headers are read, the samples are skipped.
+        assert_eq!(reader.get_mut().bytes_read - bytes_read, 5 * 240);
+
+        assert_eq!(table.len(), 5);
+        assert_eq!(table.trace_offsets, [3600, 3872, 4128, 4400, 4704]);
+        assert_eq!(table.sample_count, [8, 4, 8, 16, 8]);
+        for (index, trace) in traces.iter().enumerate() {
+            let header = &trace.header;
+            assert_eq!(table.trace_sequence_file[index], header.trace_sequence_file);
+            assert_eq!(table.inline_number[index], header.inline_number);
+            assert_eq!(table.crossline_number[index], header.crossline_number);
+            assert_eq!(table.cdp_x[index], header.cdp_x);
+            assert_eq!(table.cdp_y[index], header.cdp_y);
+            assert_eq!(table.coord_scalar[index], header.coord_scalar);
+        }
+
+        let path = temp_file("scan.sgy", &bytes);
+        let mmap_table = MmapSegyReader::open(&path).unwrap().scan_headers().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mmap_table, table);
+    }
+}
@@ -1,12 +1,28 @@
-use crate::ebcdic_syg::{BinaryHeader, Trace};
+use crate::{
+    ebcdic_syg::{BinaryHeader, EbcdicHeader, Trace},
+    stanza::TextStanza,
+};
 
 /// A structure representing the contents of the entire SEG-Y file.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SegyFile {
     /// The textual EBCDIC header stored in a single string.
     pub ebcdic_header: String,
+    /// The 3200 bytes of the textual header, written back by `write_segy_to_file`
+    /// while `ebcdic_header` is unchanged.
+    pub ebcdic_header_raw: Vec<u8>,
     /// The parsed binary header containing essential data fields.
     pub binary_header: BinaryHeader,
+    /// The extended 3200-byte textual headers (SEG-Y rev1/rev2), with the bytes
+    /// each was decoded from, written back while its text is unchanged.
+    pub extended_textual_headers: Vec<EbcdicHeader>,
+    /// The stanzas parsed from the extended textual headers.
+    pub stanzas: Vec<TextStanza>,
     /// A list of all traces found in the file, each with a header and data samples.
     pub traces: Vec<Trace>,
+    /// The SEG-Y rev2 data trailer records that follow the traces, with the bytes
+    /// each was decoded from, like `extended_textual_headers`.
+    pub trailer_records: Vec<EbcdicHeader>,
+    /// The stanzas parsed from the data trailer.
+    pub trailer_stanzas: Vec<TextStanza>,
 }
@@ -0,0 +1,200 @@
+use std::{
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{
+    errors::SegyError,
+    mapping::{FieldType, HeaderField, HeaderMapping},
+};
+
+/// Size and modification time of a SEG-Y file, saved in the sidecar files built
+/// from it (trace offset tables and trace indexes) to detect when they are stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    /// File length in bytes.
+    pub len: u64,
+    /// Modification time in seconds since the Unix epoch.
+    pub modified_secs: u64,
+    /// Sub-second part of the modification time in nanoseconds.
+    pub modified_nanos: u32,
+}
+
+impl FileStamp {
+    /// Reads the stamp of the file at `path`.
+    pub fn of<P: AsRef<Path>>(path: P) -> Result<Self, SegyError> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(FileStamp {
+            len: metadata.len(),
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+        })
+    }
+
+    /// Returns a stamp of a file whose modification time is unknown, such as one
+    /// read through a `SegyReader` without its path. It never matches `of`.
+    pub(crate) fn from_len(len: u64) -> Self {
+        FileStamp {
+            len,
+            modified_secs: 0,
+            modified_nanos: 0,
+        }
+    }
+}
+
+/// Returns the path of a sidecar file of the SEG-Y file at `path`, which is
+/// `path` with `suffix` appended.
+pub(crate) fn sidecar_path<P: AsRef<Path>>(path: P, suffix: &str) -> PathBuf {
+    let mut sidecar = path.as_ref().as_os_str().to_owned();
+    sidecar.push(suffix);
+    PathBuf::from(sidecar)
+}
+
+/// Writes the little-endian values of a sidecar file after its magic bytes.
+pub(crate) struct SidecarWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> SidecarWriter<W> {
+    /// Starts a sidecar file with its magic bytes.
+    pub(crate) fn new(mut writer: W, magic: &[u8; 8]) -> Result<Self, SegyError> {
+        writer.write_all(magic)?;
+        Ok(SidecarWriter { writer })
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) -> Result<(), SegyError> {
+        Ok(self.writer.write_all(&[value])?)
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) -> Result<(), SegyError> {
+        Ok(self.writer.write_all(&value.to_le_bytes())?)
+    }
+
+    pub(crate) fn u32(&mut self, value: u32) -> Result<(), SegyError> {
+        Ok(self.writer.write_all(&value.to_le_bytes())?)
+    }
+
+    pub(crate) fn i32(&mut self, value: i32) -> Result<(), SegyError> {
+        Ok(self.writer.write_all(&value.to_le_bytes())?)
+    }
+
+    pub(crate) fn u64(&mut self, value: u64) -> Result<(), SegyError> {
+        Ok(self.writer.write_all(&value.to_le_bytes())?)
+    }
+
+    pub(crate) fn stamp(&mut self, stamp: &FileStamp) -> Result<(), SegyError> {
+        self.u64(stamp.len)?;
+        self.u64(stamp.modified_secs)?;
+        self.u32(stamp.modified_nanos)
+    }
+
+    pub(crate) fn mapping(&mut self, mapping: &HeaderMapping) -> Result<(), SegyError> {
+        for field in [
+            mapping.inline,
+            mapping.crossline,
+            mapping.cdp_x,
+            mapping.cdp_y,
+        ] {
+            self.u16(field.byte as u16)?;
+            self.u8(field_type_code(field.field_type))?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the file.
+    pub(crate) fn finish(mut self) -> Result<(), SegyError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Reads the little-endian values written by `SidecarWriter`.
+pub(crate) struct SidecarReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> SidecarReader<R> {
+    /// Checks the magic bytes at the start of a sidecar file, failing with a
+    /// `ParseError` naming `kind` when they differ.
+    pub(crate) fn new(mut reader: R, magic: &[u8; 8], kind: &str) -> Result<Self, SegyError> {
+        let mut found = [0u8; 8];
+        reader.read_exact(&mut found)?;
+        if &found != magic {
+            return Err(SegyError::ParseError(format!("Not a SEG-Y {}", kind)));
+        }
+        Ok(SidecarReader { reader })
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], SegyError> {
+        let mut bytes = [0u8; N];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SegyError> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, SegyError> {
+        self.bytes().map(u16::from_le_bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, SegyError> {
+        self.bytes().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, SegyError> {
+        self.bytes().map(i32::from_le_bytes)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, SegyError> {
+        self.bytes().map(u64::from_le_bytes)
+    }
+
+    pub(crate) fn stamp(&mut self) -> Result<FileStamp, SegyError> {
+        Ok(FileStamp {
+            len: self.u64()?,
+            modified_secs: self.u64()?,
+            modified_nanos: self.u32()?,
+        })
+    }
+
+    pub(crate) fn mapping(&mut self) -> Result<HeaderMapping, SegyError> {
+        let mut field = || {
+            let byte = self.u16()? as usize;
+            Ok::<_, SegyError>(HeaderField::new(byte, field_type_from_code(self.u8()?)?))
+        };
+        Ok(HeaderMapping {
+            inline: field()?,
+            crossline: field()?,
+            cdp_x: field()?,
+            cdp_y: field()?,
+        })
+    }
+}
+
+fn field_type_code(field_type: FieldType) -> u8 {
+    match field_type {
+        FieldType::I16 => 1,
+        FieldType::U16 => 2,
+        FieldType::I32 => 3,
+        FieldType::U32 => 4,
+    }
+}
+
+fn field_type_from_code(code: u8) -> Result<FieldType, SegyError> {
+    match code {
+        1 => Ok(FieldType::I16),
+        2 => Ok(FieldType::U16),
+        3 => Ok(FieldType::I32),
+        4 => Ok(FieldType::U32),
+        other => Err(SegyError::ParseError(format!(
+            "Unknown header field type code: {}",
+            other
+        ))),
+    }
+}
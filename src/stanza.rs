@@ -0,0 +1,263 @@
+use crate::ebcdic_syg::EbcdicHeader;
+
+/// Width of a card image in a textual record.
+const CARD_WIDTH: usize = 80;
+/// Number of card images in a 3200-byte textual record.
+const CARDS_PER_RECORD: usize = 40;
+
+/// A named stanza from the SEG-Y rev2 extended textual headers or data trailer,
+/// e.g. `((SEG: Location Data ver 1.0))`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStanza {
+    /// The stanza name found between the double parentheses, without them.
+    pub name: String,
+    /// The raw text that follows the stanza header, up to the next stanza.
+    pub content: String,
+    /// The `key = value` pairs found in the stanza content, in order.
+    pub entries: Vec<(String, String)>,
+}
+
+impl TextStanza {
+    /// Creates a stanza from `key = value` pairs, e.g. for `SegyWriter::write_extended_stanzas`.
+    pub fn new(name: &str, entries: &[(&str, &str)]) -> Self {
+        let entries: Vec<(String, String)> = entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let content = entries
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+        TextStanza {
+            name: name.to_string(),
+            content,
+            entries,
+        }
+    }
+
+    /// Returns the `((SEG: EndText))` stanza.
+    pub fn end_text() -> Self {
+        TextStanza::new("SEG: EndText", &[])
+    }
+
+    /// Returns the value of the first entry whose key matches `key` (case-insensitive).
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns true if this is the `((SEG: EndText))` stanza.
+    pub fn is_end_text(&self) -> bool {
+        let normalized: String = self.name.chars().filter(|c| !c.is_whitespace()).collect();
+        normalized.eq_ignore_ascii_case("SEG:EndText")
+    }
+}
+
+/// Parses the stanzas contained in a sequence of 3200-byte textual records.
+///
+/// Records are concatenated before parsing since a stanza may span several of
+/// them. Lines before the first stanza header are ignored, and parsing stops at
+/// the `((SEG: EndText))` stanza.
+pub fn parse_stanzas(records: &[EbcdicHeader]) -> Vec<TextStanza> {
+    let mut stanzas: Vec<TextStanza> = Vec::new();
+    for line in records.iter().flat_map(|r| text_lines(&r.text)) {
+        if stanzas.last().is_some_and(TextStanza::is_end_text) {
+            break;
+        }
+        let trimmed = line.trim();
+        if let Some(name) = trimmed
+            .strip_prefix("((")
+            .and_then(|rest| rest.strip_suffix("))"))
+        {
+            stanzas.push(TextStanza {
+                name: name.trim().to_string(),
+                content: String::new(),
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(stanza) = stanzas.last_mut() else {
+            continue;
+        };
+        if let Some((key, value)) = trimmed.split_once('=') {
+            if !key.trim().is_empty() {
+                stanza
+                    .entries
+                    .push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        stanza.content.push_str(line.trim_end());
+        stanza.content.push('\n');
+    }
+    // Blank cards padding out a record are not part of the stanza.
+    for stanza in &mut stanzas {
+        let len = stanza.content.trim_end_matches('\n').len();
+        stanza.content.truncate(len);
+        if len > 0 {
+            stanza.content.push('\n');
+        }
+    }
+    stanzas
+}
+
+/// Lays out stanzas as 3200-byte textual records of 40 lines of 80 columns,
+/// the inverse of `parse_stanzas`.
+///
+/// Lines longer than 80 columns are wrapped. A `((SEG: EndText))` stanza starts
+/// a record of its own, as SEG-Y requires of the last extended textual header.
+pub fn stanza_records(stanzas: &[TextStanza]) -> Vec<String> {
+    let mut records: Vec<String> = Vec::new();
+    let mut lines = 0;
+    for stanza in stanzas {
+        if stanza.is_end_text() {
+            lines = 0;
+        }
+        let header = format!("(({}))", stanza.name);
+        for line in std::iter::once(header.as_str()).chain(stanza.content.lines()) {
+            let chars: Vec<char> = line.chars().collect();
+            let cards: Vec<&[char]> = if chars.is_empty() {
+                vec![&[]]
+            } else {
+                chars.chunks(CARD_WIDTH).collect()
+            };
+            for card in cards {
+                if lines == 0 {
+                    records.push(String::with_capacity(CARDS_PER_RECORD * CARD_WIDTH));
+                }
+                let record = records.last_mut().unwrap();
+                record.extend(card);
+                record.push_str(&" ".repeat(CARD_WIDTH - card.len()));
+                lines = (lines + 1) % CARDS_PER_RECORD;
+            }
+        }
+    }
+    for record in &mut records {
+        let padding = CARDS_PER_RECORD * CARD_WIDTH - record.chars().count();
+        record.push_str(&" ".repeat(padding));
+    }
+    records
+}
+
+/// Splits a textual record into lines.
+///
+/// Records are often stored as 40 card images of 80 columns without line
+/// breaks, so text without newlines is split every 80 characters.
+fn text_lines(text: &str) -> Vec<&str> {
+    if text.contains('\n') {
+        return text.lines().collect();
+    }
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest
+            .char_indices()
+            .nth(CARD_WIDTH)
+            .map_or(rest.len(), |(index, _)| index);
+        lines.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        ebcdic_syg::BinaryHeader, format::SampleFormat, reader::SegyReader, testing::sample_traces,
+        writer::SegyWriter,
+    };
+
+    fn records(texts: &[String]) -> Vec<EbcdicHeader> {
+        texts
+            .iter()
+            .map(|text| EbcdicHeader {
+                text: text.clone(),
+                raw: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stanzas_spanning_records_are_read_back() {
+        let keys: Vec<String> = (0..45).map(|index| format!("Key {}", index)).collect();
+        let entries: Vec<(&str, &str)> = keys.iter().map(|key| (key.as_str(), "value")).collect();
+        let long = TextStanza::new("SEG: Long", &entries);
+        let short = TextStanza::new("Short", &[("Datum", "WGS 84")]);
+        let stanzas = [long, short, TextStanza::end_text()];
+
+        // 46 cards of the long stanza and 2 of the short one, then EndText on its own.
+        let texts = stanza_records(&stanzas);
+        assert_eq!(texts.len(), 3);
+        assert!(texts.iter().all(|text| text.chars().count() == 3200));
+        assert!(texts[1].starts_with("Key 39 = value"));
+        assert_eq!(&texts[1][6 * 80..6 * 80 + 9], "((Short))");
+        assert!(texts[2].starts_with("((SEG: EndText))"));
+
+        assert_eq!(parse_stanzas(&records(&texts)), stanzas);
+    }
+
+    #[test]
+    fn card_images_without_newlines_are_split_every_80_columns() {
+        let cards = [
+            "C 1 CLIENT",
+            "((SEG: Location Data ver 1.0))",
+            "Datum = WGS 84",
+            "Ellipsoid = 7030",
+        ];
+        let text: String = cards.iter().map(|card| format!("{:<80}", card)).collect();
+        let text = format!("{:<3200}", text);
+
+        let stanzas = parse_stanzas(&records(&[text]));
+        assert_eq!(stanzas.len(), 1);
+        assert_eq!(stanzas[0].name, "SEG: Location Data ver 1.0");
+        assert_eq!(stanzas[0].get("datum"), Some("WGS 84"));
+        assert_eq!(stanzas[0].get("Ellipsoid"), Some("7030"));
+        assert_eq!(stanzas[0].content, "Datum = WGS 84\nEllipsoid = 7030\n");
+    }
+
+    #[test]
+    fn parsing_stops_at_end_text() {
+        let texts = stanza_records(&[
+            TextStanza::new("First", &[("A", "1")]),
+            TextStanza::end_text(),
+            TextStanza::new("After", &[("B", "2")]),
+        ]);
+        let stanzas = parse_stanzas(&records(&texts));
+        assert_eq!(stanzas.len(), 2);
+        assert_eq!(stanzas[0].get("A"), Some("1"));
+        assert!(stanzas[1].is_end_text());
+    }
+
+    #[test]
+    fn variable_extended_headers_end_at_end_text() {
+        let traces = sample_traces(3, 4);
+        let stanzas = [
+            TextStanza::new("First", &[("A", "1")]),
+            TextStanza::end_text(),
+        ];
+        let mut writer = SegyWriter::new(Cursor::new(Vec::new()));
+        writer.write_textual_header("C 1 VARIABLE").unwrap();
+        writer
+            .write_binary_header(&BinaryHeader::new(SampleFormat::IeeeFloat, 4, 4000))
+            .unwrap();
+        writer.write_extended_stanzas(&stanzas).unwrap();
+        for trace in &traces {
+            writer.write_trace(trace).unwrap();
+        }
+        let mut bytes = writer.finish().unwrap().into_inner();
+        assert_eq!(bytes[3504..3506], 2i16.to_be_bytes());
+        bytes[3504..3506].copy_from_slice(&(-1i16).to_be_bytes()); // bytes 3505-3506
+
+        let mut reader = SegyReader::open(Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.binary_header().unwrap().extended_textual_headers, -1);
+        assert_eq!(parse_stanzas(reader.extended_textual_headers()), stanzas);
+        assert_eq!(reader.data_offset().unwrap(), 3600 + 2 * 3200);
+        assert_eq!(reader.trace_count().unwrap(), 3);
+        assert_eq!(reader.trace(2).unwrap(), traces[2]);
+    }
+}
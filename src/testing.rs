@@ -0,0 +1,64 @@
+//! SEG-Y files built in memory for the unit tests.
+
+use std::{
+    io::Cursor,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    ebcdic_syg::{BinaryHeader, Trace, TraceHeader},
+    format::SampleFormat,
+    writer::SegyWriter,
+};
+
+/// Returns `count` traces of `samples` samples with distinct headers and values.
+pub(crate) fn sample_traces(count: usize, samples: usize) -> Vec<Trace> {
+    (0..count)
+        .map(|index| Trace {
+            header: TraceHeader {
+                trace_sequence_line: index as i32 + 1,
+                trace_sequence_file: index as i32 + 1,
+                inline_number: 100 + index as i32 / 3,
+                crossline_number: 200 + index as i32 % 3,
+                trace_sample_count: samples as u16,
+                ..Default::default()
+            },
+            data_samples: (0..samples)
+                .map(|sample| index as f32 * 10.0 + sample as f32 * 0.5 - 3.0)
+                .collect(),
+        })
+        .collect()
+}
+
+/// Writes a SEG-Y rev1 file of `traces` in the given sample format.
+pub(crate) fn write_file(sample_format: SampleFormat, traces: &[Trace]) -> Vec<u8> {
+    let samples = traces.first().map_or(0, |trace| trace.data_samples.len());
+    let binary_header = BinaryHeader::new(sample_format, samples as u16, 2000);
+    let mut writer = SegyWriter::new(Cursor::new(Vec::new()));
+    writer.write_textual_header("C 1 test file").unwrap();
+    writer.write_binary_header(&binary_header).unwrap();
+    for trace in traces {
+        writer.write_trace(trace).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// Writes a file of `traces` that claims revision 0 and has 0x7F junk in bytes
+/// 3507-3532, which only mean something from rev2 on.
+pub(crate) fn junk_rev1_file(traces: &[Trace]) -> Vec<u8> {
+    let mut bytes = write_file(SampleFormat::IbmFloat, traces);
+    bytes[3500] = 0; // byte 3501
+    bytes[3506..3532].fill(0x7F); // bytes 3507-3532
+    bytes
+}
+
+/// Writes `bytes` to a file in the temporary directory that no other test uses.
+pub(crate) fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let unique = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let path =
+        std::env::temp_dir().join(format!("sgy-rs-{}-{}-{}", std::process::id(), unique, name));
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
@@ -1,60 +1,777 @@
-use crate::errors::SegyError;
+use crate::{errors::SegyError, format::Endianness};
 
-pub fn read_i32_be(buffer: &[u8], offset: usize) -> Result<i32, SegyError> {
-    let end = offset + 4;
+/// Copies `N` bytes starting at `offset`, failing if the buffer is too short.
+fn read_array<const N: usize>(
+    buffer: &[u8],
+    offset: usize,
+    type_name: &str,
+) -> Result<[u8; N], SegyError> {
+    let end = offset + N;
     if end > buffer.len() {
-        return Err(SegyError::ParseError(
-            "Not enough bytes to read i32".to_string(),
-        ));
+        return Err(SegyError::ParseError(format!(
+            "Not enough bytes to read {}",
+            type_name
+        )));
     }
-    Ok(i32::from_be_bytes(buffer[offset..end].try_into().unwrap()))
+    Ok(buffer[offset..end].try_into().unwrap())
+}
+
+/// Read an `i32` in the given byte order from the buffer at the specified offset.
+pub fn read_i32(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<i32, SegyError> {
+    let bytes = read_array(buffer, offset, "i32")?;
+    Ok(match endianness {
+        Endianness::Big => i32::from_be_bytes(bytes),
+        Endianness::Little => i32::from_le_bytes(bytes),
+    })
+}
+
+/// Read a `u16` in the given byte order from the buffer at the specified offset.
+pub fn read_u16(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<u16, SegyError> {
+    let bytes = read_array(buffer, offset, "u16")?;
+    Ok(match endianness {
+        Endianness::Big => u16::from_be_bytes(bytes),
+        Endianness::Little => u16::from_le_bytes(bytes),
+    })
+}
+
+/// Read an `i16` in the given byte order from the buffer at the specified offset.
+pub fn read_i16(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<i16, SegyError> {
+    let bytes = read_array(buffer, offset, "i16")?;
+    Ok(match endianness {
+        Endianness::Big => i16::from_be_bytes(bytes),
+        Endianness::Little => i16::from_le_bytes(bytes),
+    })
+}
+
+/// Read a `u32` in the given byte order from the buffer at the specified offset.
+pub fn read_u32(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<u32, SegyError> {
+    let bytes = read_array(buffer, offset, "u32")?;
+    Ok(match endianness {
+        Endianness::Big => u32::from_be_bytes(bytes),
+        Endianness::Little => u32::from_le_bytes(bytes),
+    })
+}
+
+/// Read a `u64` in the given byte order from the buffer at the specified offset.
+pub fn read_u64(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<u64, SegyError> {
+    let bytes = read_array(buffer, offset, "u64")?;
+    Ok(match endianness {
+        Endianness::Big => u64::from_be_bytes(bytes),
+        Endianness::Little => u64::from_le_bytes(bytes),
+    })
+}
+
+/// Read an IEEE `f64` in the given byte order from the buffer at the specified offset.
+pub fn read_f64(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<f64, SegyError> {
+    let bytes = read_array(buffer, offset, "f64")?;
+    Ok(match endianness {
+        Endianness::Big => f64::from_be_bytes(bytes),
+        Endianness::Little => f64::from_le_bytes(bytes),
+    })
+}
+
+/// Read an `i64` in the given byte order from the buffer at the specified offset.
+pub fn read_i64(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<i64, SegyError> {
+    let bytes = read_array(buffer, offset, "i64")?;
+    Ok(match endianness {
+        Endianness::Big => i64::from_be_bytes(bytes),
+        Endianness::Little => i64::from_le_bytes(bytes),
+    })
+}
+
+/// Read an unsigned 24-bit integer in the given byte order from the buffer at the specified offset.
+pub fn read_u24(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<u32, SegyError> {
+    let [b0, b1, b2] = read_array(buffer, offset, "u24")?;
+    Ok(match endianness {
+        Endianness::Big => u32::from_be_bytes([0, b0, b1, b2]),
+        Endianness::Little => u32::from_le_bytes([b0, b1, b2, 0]),
+    })
+}
+
+/// Read a signed 24-bit integer in the given byte order from the buffer at the specified offset.
+pub fn read_i24(buffer: &[u8], offset: usize, endianness: Endianness) -> Result<i32, SegyError> {
+    let value = read_u24(buffer, offset, endianness)?;
+    // Shift the sign bit into place and back to sign-extend.
+    Ok(((value << 8) as i32) >> 8)
+}
+
+/// Read a big-endian `i32` from the given buffer at the specified offset.
+pub fn read_i32_be(buffer: &[u8], offset: usize) -> Result<i32, SegyError> {
+    read_i32(buffer, offset, Endianness::Big)
 }
 
 /// Read a big-endian `u16` from the given buffer at the specified offset.
 pub fn read_u16_be(buffer: &[u8], offset: usize) -> Result<u16, SegyError> {
-    let end = offset + 2;
-    if end > buffer.len() {
-        return Err(SegyError::ParseError(
-            "Not enough bytes to read u16".to_string(),
-        ));
-    }
-    Ok(u16::from_be_bytes(buffer[offset..end].try_into().unwrap()))
+    read_u16(buffer, offset, Endianness::Big)
 }
+
+/// Read a big-endian `i16` from the given buffer at the specified offset.
 pub fn read_i16_be(buffer: &[u8], offset: usize) -> Result<i16, SegyError> {
-    let end = offset + 2;
+    read_i16(buffer, offset, Endianness::Big)
+}
+
+/// Read a big-endian `u32` from the given buffer at the specified offset.
+pub fn read_u32_be(buffer: &[u8], offset: usize) -> Result<u32, SegyError> {
+    read_u32(buffer, offset, Endianness::Big)
+}
+
+/// Read a big-endian `u64` from the given buffer at the specified offset.
+pub fn read_u64_be(buffer: &[u8], offset: usize) -> Result<u64, SegyError> {
+    read_u64(buffer, offset, Endianness::Big)
+}
+
+/// Read a big-endian IEEE `f64` from the given buffer at the specified offset.
+pub fn read_f64_be(buffer: &[u8], offset: usize) -> Result<f64, SegyError> {
+    read_f64(buffer, offset, Endianness::Big)
+}
+
+/// Copies `bytes` into the buffer at `offset`, failing if the buffer is too short.
+fn write_array<const N: usize>(
+    buffer: &mut [u8],
+    offset: usize,
+    bytes: [u8; N],
+    type_name: &str,
+) -> Result<(), SegyError> {
+    let end = offset + N;
     if end > buffer.len() {
-        return Err(SegyError::ParseError(
-            "Not enough bytes to read i16".to_string(),
-        ));
+        return Err(SegyError::ParseError(format!(
+            "Not enough room to write {}",
+            type_name
+        )));
     }
-    Ok(i16::from_be_bytes(buffer[offset..end].try_into().unwrap()))
+    buffer[offset..end].copy_from_slice(&bytes);
+    Ok(())
+}
+
+/// Write an `i32` in the given byte order into the buffer at the specified offset.
+pub fn write_i32(
+    buffer: &mut [u8],
+    offset: usize,
+    value: i32,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "i32")
+}
+
+/// Write a `u16` in the given byte order into the buffer at the specified offset.
+pub fn write_u16(
+    buffer: &mut [u8],
+    offset: usize,
+    value: u16,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "u16")
+}
+
+/// Write an `i16` in the given byte order into the buffer at the specified offset.
+pub fn write_i16(
+    buffer: &mut [u8],
+    offset: usize,
+    value: i16,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "i16")
+}
+
+/// Write a `u32` in the given byte order into the buffer at the specified offset.
+pub fn write_u32(
+    buffer: &mut [u8],
+    offset: usize,
+    value: u32,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "u32")
+}
+
+/// Write a `u64` in the given byte order into the buffer at the specified offset.
+pub fn write_u64(
+    buffer: &mut [u8],
+    offset: usize,
+    value: u64,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "u64")
+}
+
+/// Write an `i64` in the given byte order into the buffer at the specified offset.
+pub fn write_i64(
+    buffer: &mut [u8],
+    offset: usize,
+    value: i64,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "i64")
+}
+
+/// Write an IEEE `f64` in the given byte order into the buffer at the specified offset.
+pub fn write_f64(
+    buffer: &mut [u8],
+    offset: usize,
+    value: f64,
+    endianness: Endianness,
+) -> Result<(), SegyError> {
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes(),
+        Endianness::Little => value.to_le_bytes(),
+    };
+    write_array(buffer, offset, bytes, "f64")
+}
+
+/// What to do with IBM floats larger than the largest finite `f32`.
+///
+/// IBM single precision reaches about 7.2e75, far beyond `f32::MAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IbmOverflow {
+    /// Convert to positive or negative infinity.
+    #[default]
+    Infinity,
+    /// Clamp to `f32::MAX` or `f32::MIN`.
+    Saturate,
+    /// Fail with `SegyError::IbmFloatOverflow`.
+    Error,
 }
 
+/// How IBM floats outside the normal IEEE single precision range are converted.
+///
+/// The default keeps denormals and converts overflowing values to infinity,
+/// which is the correctly rounded IEEE result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IbmFloatPolicy {
+    /// Convert values below `f32::MIN_POSITIVE` to signed zero instead of denormals.
+    pub flush_to_zero: bool,
+    /// What to do with values above `f32::MAX`.
+    pub overflow: IbmOverflow,
+}
 
+/// Converts a 4-byte big-endian IBM System/360 float to an IEEE `f32`.
+///
+/// The IBM value is `0.F * 16^(E - 64)`; normalizing the fraction to `1.f`
+/// gives the IEEE biased exponent `4 * (E - 64) - shifts + 126`. Values outside
+/// the `f32` range are handled as by `IbmFloatPolicy::default()`.
 pub fn ibm_to_ieee_f32(bytes: &[u8]) -> Option<f32> {
-    if bytes.len() != 4 {
-        return None;
-    }
-    let mut fraction: u32 = (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | (bytes[3] as u32);
+    let word = u32::from_be_bytes(bytes.try_into().ok()?);
+    ibm_word_to_ieee(word, IbmFloatPolicy::default()).ok()
+}
 
+/// Converts the bits of an IBM float to an IEEE `f32` using the given policy
+/// for values outside the normal `f32` range.
+///
+/// Tiny values become denormals (rounded to nearest even) or zero, keeping their
+/// sign; an IBM negative zero becomes `-0.0`.
+pub fn ibm_word_to_ieee(word: u32, policy: IbmFloatPolicy) -> Result<f32, SegyError> {
+    let sign = word & 0x8000_0000;
+    let exponent = ((word >> 24) & 0x7F) as i32;
+    let fraction = word & 0x00FF_FFFF;
     if fraction == 0 {
-        return Some(0.0);
+        return Ok(f32::from_bits(sign));
+    }
+
+    let shift = fraction.leading_zeros() - 8;
+    let biased_exponent = (exponent - 64) * 4 - shift as i32 + 126;
+    if biased_exponent >= 255 {
+        return match policy.overflow {
+            IbmOverflow::Infinity => Ok(f32::from_bits(sign | 0x7F80_0000)),
+            IbmOverflow::Saturate => Ok(f32::from_bits(sign | 0x7F7F_FFFF)),
+            IbmOverflow::Error => Err(SegyError::IbmFloatOverflow(word)),
+        };
+    }
+    if biased_exponent <= 0 {
+        if policy.flush_to_zero {
+            return Ok(f32::from_bits(sign));
+        }
+        // The value is fraction * 2^(4(E-64) - 24); express it in units of the
+        // smallest denormal, 2^-149.
+        let scale = (exponent - 64) * 4 + 125;
+        let mantissa = if scale >= 0 {
+            fraction << scale
+        } else {
+            round_shift_right(fraction, scale.unsigned_abs())
+        };
+        return Ok(f32::from_bits(sign | mantissa));
+    }
+
+    let mantissa = (fraction << shift) & 0x007F_FFFF;
+    Ok(f32::from_bits(
+        sign | (biased_exponent as u32) << 23 | mantissa,
+    ))
+}
+
+/// Shifts `value` right by `shift` bits, rounding to nearest with ties to even.
+fn round_shift_right(value: u32, shift: u32) -> u32 {
+    if shift == 0 {
+        return value;
+    }
+    if shift >= 32 {
+        return 0;
+    }
+    let quotient = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if remainder > half || (remainder == half && quotient & 1 == 1) {
+        quotient + 1
+    } else {
+        quotient
     }
+}
+
+/// Converts a slice of big-endian IBM floats to IEEE `f32`, four bytes per value.
+///
+/// Gives the same results as `ibm_to_ieee_f32` but normalizes with a
+/// leading-zero count instead of a loop, so the conversion is branch-free and
+/// can be vectorized. `bytes` must hold exactly `4 * output.len()` bytes.
+pub fn ibm_to_ieee_slice(bytes: &[u8], output: &mut [f32]) -> Result<(), SegyError> {
+    decode_ibm_slice(bytes, output, Endianness::Big, IbmFloatPolicy::default())
+}
 
-    let mut exponent = (bytes[0] & 0x7F) as i32;
-    let sign = (bytes[0] & 0x80) != 0;
+/// Converts a slice of big-endian IBM floats to IEEE `f32` using the given
+/// policy for values outside the normal `f32` range.
+pub fn ibm_to_ieee_slice_with_policy(
+    bytes: &[u8],
+    output: &mut [f32],
+    policy: IbmFloatPolicy,
+) -> Result<(), SegyError> {
+    decode_ibm_slice(bytes, output, Endianness::Big, policy)
+}
 
-    // Adjust exponent from base-16 to base-2:
-    exponent -= 64; // top bit used as sign
-    exponent *= 4;
+/// Converts a slice of IBM floats stored in the given byte order to IEEE `f32`.
+pub(crate) fn decode_ibm_slice(
+    bytes: &[u8],
+    output: &mut [f32],
+    endianness: Endianness,
+    policy: IbmFloatPolicy,
+) -> Result<(), SegyError> {
+    if bytes.len() != output.len() * 4 {
+        return Err(SegyError::ParseError(format!(
+            "Expected {} bytes of IBM floats but got {}",
+            output.len() * 4,
+            bytes.len()
+        )));
+    }
+    let read_word = match endianness {
+        Endianness::Big => u32::from_be_bytes,
+        Endianness::Little => u32::from_le_bytes,
+    };
 
-    while (fraction & 0x0080_0000) == 0 {
-        fraction <<= 1;
-        exponent -= 1;
+    // Convert the normal range branch-free, noting whether any value falls
+    // outside it, then redo only those values with the checked conversion.
+    let mut out_of_range = false;
+    for (value, chunk) in output.iter_mut().zip(bytes.chunks_exact(4)) {
+        let (bits, in_range) = ibm_word_to_ieee_bits(read_word(chunk.try_into().unwrap()));
+        *value = f32::from_bits(bits);
+        out_of_range |= !in_range;
+    }
+    if out_of_range {
+        for (value, chunk) in output.iter_mut().zip(bytes.chunks_exact(4)) {
+            let word = read_word(chunk.try_into().unwrap());
+            if !ibm_word_to_ieee_bits(word).1 {
+                *value = ibm_word_to_ieee(word, policy)?;
+            }
+        }
     }
+    Ok(())
+}
+
+/// Converts one IBM float word to the bits of an IEEE `f32` without branching,
+/// also returning whether the value is zero or within the normal `f32` range.
+#[inline]
+fn ibm_word_to_ieee_bits(word: u32) -> (u32, bool) {
+    let sign = word & 0x8000_0000;
+    let exponent = ((word >> 24) & 0x7F) as i32;
+    let fraction = word & 0x00FF_FFFF;
+
+    // Shift the leading one of the 24-bit fraction up to bit 23; a zero fraction
+    // is shifted out entirely.
+    let shift = fraction.leading_zeros() - 8;
+    let mantissa = (fraction << shift) & 0x007F_FFFF;
+    let biased_exponent = (exponent - 64) * 4 - shift as i32 + 126;
+
+    // All ones unless the fraction is zero, which gives a signed zero.
+    let non_zero = 0u32.wrapping_sub((fraction != 0) as u32);
+    let bits = sign | ((((biased_exponent as u32) << 23) | mantissa) & non_zero);
+    let in_range = fraction == 0 || (1..255).contains(&biased_exponent);
+    (bits, in_range)
+}
+
+/// What to do with NaN and infinite values, which IBM floats cannot represent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IbmNonFinite {
+    /// Encode infinities as the largest IBM magnitude (about 7.2e75) and NaN as zero.
+    #[default]
+    Saturate,
+    /// Fail with `SegyError::IbmFloatNotFinite`.
+    Error,
+}
+
+/// Converts an IEEE `f32` to a 4-byte big-endian IBM System/360 float.
+///
+/// The IBM fraction is rounded to nearest with ties to even. Every finite `f32`,
+/// including denormals, is within the IBM range; NaN and infinities are handled
+/// as by `IbmNonFinite::Saturate`.
+pub fn ieee_to_ibm(value: f32) -> [u8; 4] {
+    // Saturate never fails.
+    ieee_to_ibm_word(value, IbmNonFinite::Saturate)
+        .unwrap_or_default()
+        .to_be_bytes()
+}
+
+/// Converts an IEEE `f32` to the bits of an IBM float using the given policy
+/// for NaN and infinities.
+///
+/// Zeros keep their sign, so `-0.0` becomes the IBM negative zero `0x80000000`.
+pub fn ieee_to_ibm_word(value: f32, non_finite: IbmNonFinite) -> Result<u32, SegyError> {
+    let bits = value.to_bits();
+    let sign = bits & 0x8000_0000;
+    if !value.is_finite() {
+        return match non_finite {
+            IbmNonFinite::Saturate if value.is_nan() => Ok(0),
+            IbmNonFinite::Saturate => Ok(sign | 0x7FFF_FFFF),
+            IbmNonFinite::Error => Err(SegyError::IbmFloatNotFinite(value)),
+        };
+    }
+
+    let biased_exponent = ((bits >> 23) & 0xFF) as i32;
+    let mut mantissa = bits & 0x007F_FFFF;
+    if biased_exponent != 0 {
+        mantissa |= 0x0080_0000;
+    } else if mantissa == 0 {
+        return Ok(sign);
+    }
+
+    // The value is mantissa * 2^power with the mantissa normalized to 24 bits.
+    let shift = mantissa.leading_zeros() - 8;
+    mantissa <<= shift;
+    let power = biased_exponent.max(1) - 150 - shift as i32;
+
+    // Choose the smallest hex exponent so that the fraction fits in 24 bits,
+    // which drops at most three low bits of the mantissa. Dropping any bit leaves
+    // at most 23, so a rounding carry never overflows the fraction.
+    let hex_power = (power + 3).div_euclid(4);
+    let fraction = round_shift_right(mantissa, (hex_power * 4 - power) as u32);
+    debug_assert!(fraction < 0x0100_0000);
+    // The value is fraction * 16^(E - 64 - 6).
+    let exponent = (hex_power + 70) as u32;
+    Ok(sign | exponent << 24 | fraction)
+}
+
+/// Converts a slice of IEEE `f32` to big-endian IBM floats, four bytes per value.
+///
+/// `output` must hold exactly `4 * values.len()` bytes.
+pub fn ieee_to_ibm_slice(values: &[f32], output: &mut [u8]) -> Result<(), SegyError> {
+    encode_ibm_slice(values, output, Endianness::Big, IbmNonFinite::default())
+}
+
+/// Converts a slice of IEEE `f32` to big-endian IBM floats using the given
+/// policy for NaN and infinities.
+pub fn ieee_to_ibm_slice_with_policy(
+    values: &[f32],
+    output: &mut [u8],
+    non_finite: IbmNonFinite,
+) -> Result<(), SegyError> {
+    encode_ibm_slice(values, output, Endianness::Big, non_finite)
+}
+
+/// Converts a slice of IEEE `f32` to IBM floats stored in the given byte order.
+pub(crate) fn encode_ibm_slice(
+    values: &[f32],
+    output: &mut [u8],
+    endianness: Endianness,
+    non_finite: IbmNonFinite,
+) -> Result<(), SegyError> {
+    if output.len() != values.len() * 4 {
+        return Err(SegyError::ParseError(format!(
+            "Expected {} bytes for IBM floats but got {}",
+            values.len() * 4,
+            output.len()
+        )));
+    }
+    for (value, chunk) in values.iter().zip(output.chunks_exact_mut(4)) {
+        let word = ieee_to_ibm_word(*value, non_finite)?;
+        chunk.copy_from_slice(&match endianness {
+            Endianness::Big => word.to_be_bytes(),
+            Endianness::Little => word.to_le_bytes(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fraction &= 0x007F_FFFF;
-    let sign_bit = (sign as u32) << 31;
-    let exponent_bits = ((exponent + 127) as u32) << 23;
-    Some(f32::from_bits(sign_bit | exponent_bits | fraction))
+    /// The correctly rounded `f32` value of an IBM float word: the product is
+    /// exact in `f64`, so the cast is the only rounding.
+    fn ibm_reference(word: u32) -> f32 {
+        let exponent = ((word >> 24) & 0x7F) as i32;
+        let fraction = (word & 0x00FF_FFFF) as f64;
+        let magnitude = fraction * 2f64.powi(4 * (exponent - 64) - 24);
+        let value = if word & 0x8000_0000 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        };
+        value as f32
+    }
+
+    /// Decodes `words` with the slice conversion, in both byte orders.
+    fn decode_words(words: &[u32], policy: IbmFloatPolicy) -> Vec<f32> {
+        let big: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes()).collect();
+        let little: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let mut output = vec![0f32; words.len()];
+        let mut little_output = vec![0f32; words.len()];
+        decode_ibm_slice(&big, &mut output, Endianness::Big, policy).unwrap();
+        decode_ibm_slice(&little, &mut little_output, Endianness::Little, policy).unwrap();
+        let bits = |values: &[f32]| values.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&output), bits(&little_output));
+        output
+    }
+
+    /// Checks that the slice and branch-free conversions give the same bits as
+    /// `ibm_word_to_ieee` for every word in `words`.
+    fn assert_slice_matches_scalar(words: &[u32]) {
+        let decoded = decode_words(words, IbmFloatPolicy::default());
+        for (&word, value) in words.iter().zip(decoded) {
+            let scalar = ibm_word_to_ieee(word, IbmFloatPolicy::default()).unwrap();
+            assert_eq!(value.to_bits(), scalar.to_bits(), "word {:#010x}", word);
+            let (bits, in_range) = ibm_word_to_ieee_bits(word);
+            if in_range {
+                assert_eq!(bits, scalar.to_bits(), "word {:#010x}", word);
+            }
+        }
+    }
+
+    #[test]
+    fn slice_conversion_matches_scalar_conversion() {
+        let mut words = Vec::new();
+        for sign in [0, 0x8000_0000] {
+            for exponent in 0..=127u32 {
+                for fraction in [0, 1, 0x0F_FFFF, 0x10_0000, 0xFF_FFFF] {
+                    words.push(sign | exponent << 24 | fraction);
+                }
+            }
+        }
+        assert_slice_matches_scalar(&words);
+
+        // The default policy gives the correctly rounded result, including
+        // denormals and infinities at both ends of the exponent range.
+        for &word in &words {
+            let scalar = ibm_word_to_ieee(word, IbmFloatPolicy::default()).unwrap();
+            assert_eq!(
+                scalar.to_bits(),
+                ibm_reference(word).to_bits(),
+                "word {:#010x}",
+                word
+            );
+        }
+    }
+
+    /// Encodes `value` and decodes it back with the default policy.
+    fn round_trip(value: f32) -> f32 {
+        let word = ieee_to_ibm_word(value, IbmNonFinite::Saturate).unwrap();
+        ibm_word_to_ieee(word, IbmFloatPolicy::default()).unwrap()
+    }
+
+    #[test]
+    fn encoding_rounds_ties_to_even() {
+        // 1 + 2^-21 needs a 24-bit fraction shifted right by three: the dropped
+        // bits are exactly half, and the even fraction is kept.
+        let tie_down = 1.0 + f32::EPSILON * 4.0;
+        assert_eq!(
+            ieee_to_ibm_word(tie_down, IbmNonFinite::Saturate).unwrap(),
+            0x4110_0000
+        );
+        assert_eq!(round_trip(tie_down), 1.0);
+        // 1 + 3 * 2^-21 is also a tie, and rounds up to the even fraction.
+        let tie_up = 1.0 + f32::EPSILON * 12.0;
+        assert_eq!(
+            ieee_to_ibm_word(tie_up, IbmNonFinite::Saturate).unwrap(),
+            0x4110_0002
+        );
+        assert_eq!(round_trip(tie_up), 1.0 + f32::EPSILON * 16.0);
+    }
+
+    #[test]
+    fn encoding_carries_into_the_next_fraction_bit() {
+        // A 24-bit mantissa of all ones shifted right by one, two or three bits
+        // rounds up to the next power of two, still within the 24-bit fraction.
+        let all_ones = 2.0 - f32::EPSILON;
+        for (value, word) in [
+            (all_ones, 0x4120_0000),
+            (all_ones * 2.0, 0x4140_0000),
+            (all_ones * 4.0, 0x4180_0000),
+        ] {
+            assert_eq!(
+                ieee_to_ibm_word(value, IbmNonFinite::Saturate).unwrap(),
+                word
+            );
+            assert_eq!(
+                round_trip(value),
+                value + f32::EPSILON * (value / 2.0).ceil()
+            );
+        }
+        // With nothing to drop, the fraction is all ones.
+        let exact = all_ones * 8.0;
+        assert_eq!(
+            ieee_to_ibm_word(exact, IbmNonFinite::Saturate).unwrap(),
+            0x41FF_FFFF
+        );
+        assert_eq!(round_trip(exact), exact);
+    }
+
+    #[test]
+    fn encoding_keeps_zeros_denormals_and_extremes() {
+        assert_eq!(ieee_to_ibm_word(0.0, IbmNonFinite::Saturate).unwrap(), 0);
+        assert_eq!(
+            ieee_to_ibm_word(-0.0, IbmNonFinite::Saturate).unwrap(),
+            0x8000_0000
+        );
+        assert_eq!(round_trip(-0.0).to_bits(), (-0.0f32).to_bits());
+
+        // Denormals with up to 21 significant bits are exact in IBM; longer ones
+        // are rounded like normal values.
+        for bits in [1, 2, 0x40_0000, 0x1F_FFFF, 0x8000_0001, 0x801F_FFFF] {
+            let value = f32::from_bits(bits);
+            assert_eq!(round_trip(value).to_bits(), bits);
+        }
+        assert_eq!(round_trip(f32::from_bits(0x7F_FFFF)), f32::MIN_POSITIVE);
+
+        assert_eq!(
+            ieee_to_ibm_word(f32::MAX, IbmNonFinite::Saturate).unwrap(),
+            0x60FF_FFFF
+        );
+        assert_eq!(round_trip(f32::MAX), f32::MAX);
+        assert_eq!(round_trip(f32::MIN), f32::MIN);
+        assert_eq!(round_trip(f32::MIN_POSITIVE), f32::MIN_POSITIVE);
+    }
+
+    #[test]
+    fn encoding_handles_non_finite_values_by_policy() {
+        let values = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY];
+        let saturated = [0, 0x7FFF_FFFF, 0xFFFF_FFFF];
+        for (&value, &word) in values.iter().zip(&saturated) {
+            assert_eq!(
+                ieee_to_ibm_word(value, IbmNonFinite::Saturate).unwrap(),
+                word
+            );
+            assert!(matches!(
+                ieee_to_ibm_word(value, IbmNonFinite::Error),
+                Err(SegyError::IbmFloatNotFinite(_))
+            ));
+        }
+        assert_eq!(ieee_to_ibm(f32::INFINITY), 0x7FFF_FFFFu32.to_be_bytes());
+
+        // The largest IBM magnitude overflows `f32` and decodes to infinity.
+        let decoded = decode_words(&saturated, IbmFloatPolicy::default());
+        assert_eq!(decoded[0], 0.0);
+        assert_eq!(decoded[1], f32::INFINITY);
+        assert_eq!(decoded[2], f32::NEG_INFINITY);
+
+        let mut output = vec![0u8; 12];
+        ieee_to_ibm_slice(&values, &mut output).unwrap();
+        let expected: Vec<u8> = saturated.iter().flat_map(|w| w.to_be_bytes()).collect();
+        assert_eq!(output, expected);
+        assert!(ieee_to_ibm_slice_with_policy(&values, &mut output, IbmNonFinite::Error).is_err());
+    }
+
+    #[test]
+    fn slice_encoding_round_trips_through_the_decoder() {
+        // Every exponent, with a spread of mantissas; IBM keeps at least 21
+        // significant bits, so the round trip is within 2^-21 of the value.
+        let values: Vec<f32> = (0..=0xFFu32)
+            .flat_map(|exponent| {
+                [0, 1, 0x12_3457, 0x55_5555, 0x7F_FFFF]
+                    .into_iter()
+                    .flat_map(move |mantissa| {
+                        let bits = exponent << 23 | mantissa;
+                        [bits, bits | 0x8000_0000]
+                    })
+            })
+            .map(f32::from_bits)
+            .filter(|value| value.is_finite())
+            .collect();
+
+        let mut bytes = vec![0u8; values.len() * 4];
+        ieee_to_ibm_slice(&values, &mut bytes).unwrap();
+        let mut little = vec![0u8; values.len() * 4];
+        encode_ibm_slice(
+            &values,
+            &mut little,
+            Endianness::Little,
+            IbmNonFinite::Saturate,
+        )
+        .unwrap();
+        let mut decoded = vec![0f32; values.len()];
+        ibm_to_ieee_slice(&bytes, &mut decoded).unwrap();
+
+        for (i, (&value, &back)) in values.iter().zip(&decoded).enumerate() {
+            let word = ieee_to_ibm_word(value, IbmNonFinite::Saturate).unwrap();
+            assert_eq!(bytes[i * 4..i * 4 + 4], word.to_be_bytes());
+            assert_eq!(little[i * 4..i * 4 + 4], word.to_le_bytes());
+            assert_eq!(back, round_trip(value));
+            assert!(
+                (back - value).abs() <= value.abs() * 2f32.powi(-21),
+                "{:e} came back as {:e}",
+                value,
+                back
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "converts all 2^32 IBM words"]
+    fn slice_conversion_matches_scalar_conversion_exhaustively() {
+        let mut words = Vec::with_capacity(1 << 20);
+        for high in 0..(1u32 << 12) {
+            words.clear();
+            words.extend((0..(1u32 << 20)).map(|low| high << 20 | low));
+            assert_slice_matches_scalar(&words);
+        }
+    }
+
+    #[test]
+    fn ibm_floats_decode_to_their_value() {
+        // An exponent bias of 127 instead of 126 once decoded every sample to
+        // twice its value.
+        let cases = [
+            (0x4110_0000, 1.0),
+            (0xC110_0000, -1.0),
+            (0x4080_0000, 0.5),
+            (0x4264_0000, 100.0),
+            (0xC276_A000, -118.625),
+            (0x4110_0001, 1.0 + f32::EPSILON * 8.0),
+        ];
+        let words: Vec<u32> = cases.iter().map(|&(word, _)| word).collect();
+        let decoded = decode_words(&words, IbmFloatPolicy::default());
+        for (&(word, expected), sliced) in cases.iter().zip(decoded) {
+            assert_eq!(ibm_to_ieee_f32(&word.to_be_bytes()), Some(expected));
+            assert_eq!(
+                ibm_word_to_ieee(word, IbmFloatPolicy::default()).unwrap(),
+                expected
+            );
+            assert_eq!(sliced, expected);
+        }
+    }
 }
@@ -1,11 +1,8 @@
 /// The size (in bytes) of each trace header in a SEG-Y file.
-
 pub const TRACE_HEADER_SIZE: usize = 240;
 
-/// The size  of the textual EBCDIC header in a SEG-Y file.
-
+/// The size (in bytes) of the textual EBCDIC header in a SEG-Y file.
 pub const EBCDIC_HEADER_SIZE: usize = 3200;
 
 /// The size (in bytes) of the SEG-Y binary header.
-
 pub const BINARY_HEADER_SIZE: usize = 400;
@@ -0,0 +1,234 @@
+use std::io::{Read, Seek};
+
+use crate::{errors::SegyError, geometry::SurveyGeometry, reader::SegyReader};
+
+/// A 2D array of samples stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// Number of rows.
+    pub rows: usize,
+    /// Number of columns.
+    pub columns: usize,
+    /// The samples, `columns` values per row.
+    pub data: Vec<f32>,
+}
+
+impl Section {
+    /// Returns the sample at (`row`, `column`).
+    pub fn get(&self, row: usize, column: usize) -> Option<f32> {
+        if row >= self.rows || column >= self.columns {
+            return None;
+        }
+        Some(self.data[row * self.columns + column])
+    }
+
+    /// Returns the samples of one row.
+    pub fn row(&self, row: usize) -> &[f32] {
+        &self.data[row * self.columns..(row + 1) * self.columns]
+    }
+}
+
+/// A post-stack 3D volume, giving access to in-line and cross-line sections
+/// and depth (or time) slices by seeking to only the traces needed.
+pub struct Volume<R: Read + Seek> {
+    reader: SegyReader<R>,
+    geometry: SurveyGeometry,
+    samples_per_trace: usize,
+    fill_value: f32,
+}
+
+impl<R: Read + Seek> Volume<R> {
+    /// Scans the trace headers of an opened reader to infer the survey geometry.
+    ///
+    /// The reader must have read the file headers, e.g. with `SegyReader::open`.
+    pub fn new(mut reader: SegyReader<R>) -> Result<Self, SegyError> {
+        let geometry = SurveyGeometry::from_table(&reader.scan_headers()?)?;
+        Self::with_geometry(reader, geometry)
+    }
+
+    /// Wraps a reader whose geometry is already known.
+    pub fn with_geometry(
+        reader: SegyReader<R>,
+        geometry: SurveyGeometry,
+    ) -> Result<Self, SegyError> {
+        let samples_per_trace = reader
+            .binary_header()
+            .ok_or_else(|| SegyError::ParseError("Binary header has not been read".to_string()))?
+            .samples_per_trace as usize;
+        Ok(Volume {
+            reader,
+            geometry,
+            samples_per_trace,
+            fill_value: 0.0,
+        })
+    }
+
+    /// Returns the survey geometry.
+    pub fn geometry(&self) -> &SurveyGeometry {
+        &self.geometry
+    }
+
+    /// Sets the value used for grid positions without a trace (0.0 by default).
+    pub fn set_fill_value(&mut self, fill_value: f32) {
+        self.fill_value = fill_value;
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_reader(self) -> SegyReader<R> {
+        self.reader
+    }
+
+    /// Reads the in-line section `inline`: one row per cross-line, one column per sample.
+    ///
+    /// Sections have as many columns as the binary header's samples per trace;
+    /// shorter traces are padded with the fill value and longer ones are an error.
+    pub fn inline(&mut self, inline: i32) -> Result<Section, SegyError> {
+        if self.geometry.inlines.position(inline).is_none() {
+            return Err(SegyError::ParseError(format!(
+                "In-line {} is not part of the survey",
+                inline
+            )));
+        }
+        let crosslines = self.geometry.crosslines;
+        let indices = (0..crosslines.len())
+            .map(|position| self.geometry.trace_index(inline, crosslines.line(position)))
+            .collect::<Vec<_>>();
+        self.read_section(&indices)
+    }
+
+    /// Reads the cross-line section `crossline`: one row per in-line, one column per sample.
+    ///
+    /// Traces are padded or rejected as by `inline`.
+    pub fn crossline(&mut self, crossline: i32) -> Result<Section, SegyError> {
+        if self.geometry.crosslines.position(crossline).is_none() {
+            return Err(SegyError::ParseError(format!(
+                "Cross-line {} is not part of the survey",
+                crossline
+            )));
+        }
+        let inlines = self.geometry.inlines;
+        let indices = (0..inlines.len())
+            .map(|position| self.geometry.trace_index(inlines.line(position), crossline))
+            .collect::<Vec<_>>();
+        self.read_section(&indices)
+    }
+
+    /// Reads the horizontal slice at `sample_index`: one row per in-line and one
+    /// column per cross-line, reading a single sample from each trace.
+    pub fn depth_slice(&mut self, sample_index: usize) -> Result<Section, SegyError> {
+        let inlines = self.geometry.inlines;
+        let crosslines = self.geometry.crosslines;
+        let mut data = Vec::with_capacity(inlines.len() * crosslines.len());
+        for inline_position in 0..inlines.len() {
+            for crossline_position in 0..crosslines.len() {
+                let index = self.geometry.trace_index(
+                    inlines.line(inline_position),
+                    crosslines.line(crossline_position),
+                );
+                data.push(match index {
+                    Some(index) => self.reader.trace_sample(index, sample_index)?,
+                    None => self.fill_value,
+                });
+            }
+        }
+        Ok(Section {
+            rows: inlines.len(),
+            columns: crosslines.len(),
+            data,
+        })
+    }
+
+    /// Reads the given traces as the rows of a section, filling missing traces
+    /// and the end of short ones.
+    fn read_section(&mut self, indices: &[Option<usize>]) -> Result<Section, SegyError> {
+        let columns = self.samples_per_trace;
+        let mut data = Vec::with_capacity(indices.len() * columns);
+        for index in indices {
+            match index {
+                Some(index) => {
+                    let mut samples = self.reader.trace(*index)?.data_samples;
+                    if samples.len() > columns {
+                        return Err(SegyError::ParseError(format!(
+                            "Trace {} has {} samples, more than the {} of the section",
+                            index,
+                            samples.len(),
+                            columns
+                        )));
+                    }
+                    samples.resize(columns, self.fill_value);
+                    data.extend(samples);
+                }
+                None => data.resize(data.len() + columns, self.fill_value),
+            }
+        }
+        Ok(Section {
+            rows: indices.len(),
+            columns,
+            data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, SeekFrom};
+
+    use super::*;
+    use crate::{
+        format::SampleFormat,
+        testing::{sample_traces, write_file},
+    };
+
+    /// Counts the seeks to the end of the file made through it.
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        seeks_to_end: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            if let SeekFrom::End(_) = pos {
+                self.seeks_to_end += 1;
+            }
+            self.inner.seek(pos)
+        }
+    }
+
+    fn open(bytes: Vec<u8>) -> Volume<CountingReader> {
+        let reader = SegyReader::open(CountingReader {
+            inner: Cursor::new(bytes),
+            seeks_to_end: 0,
+        })
+        .unwrap();
+        Volume::new(reader).unwrap()
+    }
+
+    #[test]
+    fn depth_slices_seek_to_the_end_of_the_file_once() {
+        let traces = sample_traces(9, 4);
+        let mut volume = open(write_file(SampleFormat::IeeeFloat, &traces));
+        let slice = volume.depth_slice(2).unwrap();
+        assert_eq!((slice.rows, slice.columns), (3, 3));
+        assert_eq!(slice.get(1, 2), Some(traces[5].data_samples[2]));
+        volume.depth_slice(3).unwrap();
+        assert!(volume.into_reader().get_mut().seeks_to_end <= 1);
+    }
+
+    #[test]
+    fn sections_reject_traces_longer_than_the_binary_header_says() {
+        let mut traces = sample_traces(9, 4);
+        traces[4].data_samples.push(1.0);
+        traces[4].header.trace_sample_count = 5;
+        let bytes = write_file(SampleFormat::IeeeFloat, &traces);
+        assert_eq!(bytes[3502..3504], [0, 0]); // bytes 3503-3504, the fixed-length flag
+        let mut volume = open(bytes);
+        assert_eq!(volume.inline(100).unwrap().row(2), traces[2].data_samples);
+        assert!(matches!(volume.inline(101), Err(SegyError::ParseError(_))));
+    }
+}
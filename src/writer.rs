@@ -0,0 +1,945 @@
+use std::io::{Seek, SeekFrom, Write};
+
+use ebcdic::ebcdic::Ebcdic;
+
+use crate::{
+    ebcdic_syg::{
+        BinaryHeader, EbcdicHeader, Trace, TraceHeader, TraceHeaderExtension1, TypedTrace,
+    },
+    edit::{binary_header_fields, TRACE_HEADER_FIELDS},
+    errors::SegyError,
+    format::Endianness,
+    mapping::HeaderMapping,
+    reader::{decode_textual_header, parse_binary_header, parse_trace_header, TraceLayout},
+    samples::TraceSamples,
+    stanza::{stanza_records, TextStanza},
+    utils::{
+        write_f64, write_i16, write_i32, write_i64, write_u16, write_u32, write_u64, IbmNonFinite,
+    },
+    value::{BINARY_HEADER_SIZE, EBCDIC_HEADER_SIZE, TRACE_HEADER_SIZE},
+};
+
+/// The SEG-Y rev2 byte-order constant, written in the file's byte order.
+const BYTE_ORDER_CONSTANT: u32 = 0x0102_0304;
+/// Name in bytes 233-240 of a SEG-Y rev2 trace header extension 1.
+const EXTENSION_1_NAME: &[u8; 8] = b"SEG00001";
+
+/// Character set of the textual headers written by `SegyWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    /// EBCDIC, as required by SEG-Y rev1.
+    #[default]
+    Ebcdic,
+    /// ASCII, allowed by SEG-Y rev2.
+    Ascii,
+}
+
+/// SEG-Y revision whose layout `SegyWriter` produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegyRevision {
+    /// Write the binary header as given.
+    #[default]
+    Rev1,
+    /// Mark the binary header as rev2, with the byte-order constant (bytes
+    /// 3297-3300), extended samples per trace (bytes 3269-3272), revision number
+    /// (bytes 3501-3502) and one trace header extension per trace (bytes 3507-3510).
+    Rev2,
+}
+
+/// Writes SEG-Y files: the textual header, the binary header, any extended
+/// textual headers, the traces and, for rev2, the data trailer, in that order.
+///
+/// Binary values are written in the byte order of the binary header, and
+/// samples are encoded in its sample format. `finish` rewrites the binary header
+/// so that its counts match what was written: the number of extended textual
+/// headers (bytes 3505-3506) and, for rev2 headers, the number of traces, the
+/// offset of the first trace and the number of trailer records (bytes 3513-3532).
+/// It also clears the fixed-length trace flag (bytes 3503-3504) if a trace did
+/// not have the binary header's number of samples.
+pub struct SegyWriter<W: Write + Seek> {
+    writer: W,
+    /// Byte order of the binary header, used for everything written after it.
+    endianness: Endianness,
+    /// Character set of the textual headers.
+    text_encoding: TextEncoding,
+    /// Locations of the in-line, cross-line and CDP coordinate trace header fields.
+    header_mapping: HeaderMapping,
+    /// How NaN and infinite samples are written as IBM floats.
+    ibm_non_finite: IbmNonFinite,
+    /// SEG-Y revision of the files written.
+    revision: SegyRevision,
+    /// The binary header as written, kept to update its counts in `finish`.
+    binary_header: Option<BinaryHeader>,
+    /// Byte offset of the binary header in the destination.
+    binary_header_offset: u64,
+    /// Sample format and default trace length, once the binary header is written.
+    layout: Option<TraceLayout>,
+    /// Number of extended textual headers written so far.
+    extended_headers_written: u16,
+    /// Byte offset of the first trace, once the extended textual headers are done.
+    first_trace_offset: Option<u64>,
+    /// Number of traces written so far.
+    traces_written: u64,
+    /// Whether a trace written had another length than the binary header's.
+    variable_length_traces: bool,
+    /// Number of data trailer records written so far.
+    trailer_records_written: u32,
+}
+
+impl<W: Write + Seek> SegyWriter<W> {
+    /// Creates a new `SegyWriter` writing to any `Write + Seek` destination.
+    pub fn new(writer: W) -> Self {
+        SegyWriter {
+            writer,
+            endianness: Endianness::Big,
+            text_encoding: TextEncoding::default(),
+            header_mapping: HeaderMapping::default(),
+            ibm_non_finite: IbmNonFinite::default(),
+            revision: SegyRevision::default(),
+            binary_header: None,
+            binary_header_offset: 0,
+            layout: None,
+            extended_headers_written: 0,
+            first_trace_offset: None,
+            traces_written: 0,
+            variable_length_traces: false,
+            trailer_records_written: 0,
+        }
+    }
+
+    /// Returns the SEG-Y revision of the files written.
+    pub fn revision(&self) -> SegyRevision {
+        self.revision
+    }
+
+    /// Sets the SEG-Y revision of the files written. It takes effect when the
+    /// binary header is written.
+    pub fn set_revision(&mut self, revision: SegyRevision) {
+        self.revision = revision;
+    }
+
+    /// Returns the character set used for textual headers.
+    pub fn text_encoding(&self) -> TextEncoding {
+        self.text_encoding
+    }
+
+    /// Sets the character set used for textual headers.
+    pub fn set_text_encoding(&mut self, text_encoding: TextEncoding) {
+        self.text_encoding = text_encoding;
+    }
+
+    /// Returns the trace header locations used for in-line, cross-line and CDP X/Y.
+    pub fn header_mapping(&self) -> &HeaderMapping {
+        &self.header_mapping
+    }
+
+    /// Sets the trace header locations the in-line, cross-line and CDP X/Y
+    /// numbers are written to.
+    pub fn set_header_mapping(&mut self, header_mapping: HeaderMapping) {
+        self.header_mapping = header_mapping;
+    }
+
+    /// Returns how NaN and infinite samples are written as IBM floats.
+    pub fn ibm_non_finite(&self) -> IbmNonFinite {
+        self.ibm_non_finite
+    }
+
+    /// Sets how NaN and infinite samples are written as IBM floats.
+    pub fn set_ibm_non_finite(&mut self, ibm_non_finite: IbmNonFinite) {
+        self.ibm_non_finite = ibm_non_finite;
+    }
+
+    /// Returns the number of traces written so far.
+    pub fn traces_written(&self) -> u64 {
+        self.traces_written
+    }
+
+    /// Writes a 3200-byte textual header, padding `text` with spaces.
+    pub fn write_textual_header(&mut self, text: &str) -> Result<(), SegyError> {
+        self.write_text_record(text, &[])
+    }
+
+    /// Writes a textual header as read from a file, reusing its raw bytes when
+    /// its text is unchanged so that it is reproduced exactly.
+    pub fn write_ebcdic_header(&mut self, header: &EbcdicHeader) -> Result<(), SegyError> {
+        self.write_text_record(&header.text, &header.raw)
+    }
+
+    /// Writes the 400-byte binary header.
+    ///
+    /// Its byte order, sample format, samples per trace and number of trace
+    /// header extensions are used for everything written afterwards. In
+    /// `SegyRevision::Rev2` mode the header is first marked as rev2.
+    pub fn write_binary_header(&mut self, binary_header: &BinaryHeader) -> Result<(), SegyError> {
+        let mut binary_header = binary_header.clone();
+        if self.revision == SegyRevision::Rev2 {
+            binary_header.byte_order_constant = BYTE_ORDER_CONSTANT;
+            if binary_header.segy_revision_major < 2 {
+                binary_header.segy_revision_major = 2;
+                binary_header.segy_revision_minor = 0;
+            }
+            if binary_header.extended_samples_per_trace == 0 {
+                binary_header.extended_samples_per_trace = binary_header.samples_per_trace as u32;
+            }
+            binary_header.max_additional_trace_headers =
+                binary_header.max_additional_trace_headers.max(1);
+        }
+        let buffer = encode_binary_header(&binary_header)?;
+        self.binary_header_offset = self.writer.stream_position()?;
+        self.writer.write_all(&buffer)?;
+        self.endianness = binary_header.endianness;
+        self.layout = Some(TraceLayout::from(&binary_header));
+        self.binary_header = Some(binary_header);
+        self.extended_headers_written = 0;
+        self.first_trace_offset = None;
+        Ok(())
+    }
+
+    /// Writes one 3200-byte extended textual header, padding `text` with spaces.
+    pub fn write_extended_textual_header(&mut self, text: &str) -> Result<(), SegyError> {
+        self.write_extended_record(text, &[])
+    }
+
+    /// Writes an extended textual header as read from a file, reusing its raw
+    /// bytes when its text is unchanged.
+    pub fn write_extended_ebcdic_header(&mut self, header: &EbcdicHeader) -> Result<(), SegyError> {
+        self.write_extended_record(&header.text, &header.raw)
+    }
+
+    /// Writes stanzas as extended textual headers, 40 lines of 80 columns per
+    /// record, followed by a closing `((SEG: EndText))` record unless the last
+    /// stanza is already one.
+    pub fn write_extended_stanzas(&mut self, stanzas: &[TextStanza]) -> Result<(), SegyError> {
+        let mut stanzas = stanzas.to_vec();
+        if !stanzas.last().is_some_and(TextStanza::is_end_text) {
+            stanzas.push(TextStanza::end_text());
+        }
+        for record in stanza_records(&stanzas) {
+            self.write_extended_textual_header(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Writes one trace, encoding its `f32` samples in the file's sample format.
+    ///
+    /// When the binary header announces trace header extensions, extension 1 is
+    /// derived from the trace header with `TraceHeaderExtension1::from_trace_header`.
+    pub fn write_trace(&mut self, trace: &Trace) -> Result<(), SegyError> {
+        let samples = TraceSamples::F32(trace.data_samples.clone());
+        self.write_trace_parts(&trace.header, None, &samples)
+    }
+
+    /// Writes one trace whose samples are in their native type, converting them
+    /// to the file's sample format if needed.
+    pub fn write_typed_trace(&mut self, trace: &TypedTrace) -> Result<(), SegyError> {
+        self.write_trace_parts(&trace.header, None, &trace.samples)
+    }
+
+    /// Writes one trace with the given trace header extension 1.
+    ///
+    /// The binary header must announce at least one additional trace header.
+    pub fn write_trace_with_extension(
+        &mut self,
+        trace: &Trace,
+        extension: &TraceHeaderExtension1,
+    ) -> Result<(), SegyError> {
+        let samples = TraceSamples::F32(trace.data_samples.clone());
+        self.write_trace_parts(&trace.header, Some(extension), &samples)
+    }
+
+    /// Writes one 3200-byte data trailer record after the traces, padding `text`
+    /// with spaces. Only rev2 files have a data trailer.
+    pub fn write_trailer_record(&mut self, text: &str) -> Result<(), SegyError> {
+        self.write_trailer_text_record(text, &[])
+    }
+
+    /// Writes a data trailer record as read from a file, reusing its raw bytes
+    /// when its text is unchanged.
+    pub fn write_trailer_ebcdic_record(&mut self, record: &EbcdicHeader) -> Result<(), SegyError> {
+        self.write_trailer_text_record(&record.text, &record.raw)
+    }
+
+    /// Writes stanzas as data trailer records, 40 lines of 80 columns per record.
+    pub fn write_trailer_stanzas(&mut self, stanzas: &[TextStanza]) -> Result<(), SegyError> {
+        for record in stanza_records(stanzas) {
+            self.write_trailer_record(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Updates the counts in the binary header to match what was written, then
+    /// flushes and returns the underlying destination.
+    pub fn finish(mut self) -> Result<W, SegyError> {
+        if let Some(mut binary_header) = self.binary_header.take() {
+            let first_trace_offset = self.start_traces()?;
+            binary_header.extended_textual_headers = self.extended_headers_written as i16;
+            if self.variable_length_traces {
+                binary_header.fixed_length_traces = 0;
+            }
+            if binary_header.segy_revision_major >= 2 {
+                binary_header.traces_in_file = self.traces_written;
+                binary_header.first_trace_offset = first_trace_offset;
+                binary_header.trailer_stanza_records = self.trailer_records_written as i32;
+            }
+            let end = self.writer.stream_position()?;
+            self.writer
+                .seek(SeekFrom::Start(self.binary_header_offset))?;
+            self.writer
+                .write_all(&encode_binary_header(&binary_header)?)?;
+            self.writer.seek(SeekFrom::Start(end))?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Writes a 3200-byte textual record: `raw` when it decodes to `text`,
+    /// otherwise `text` padded with spaces.
+    fn write_text_record(&mut self, text: &str, raw: &[u8]) -> Result<(), SegyError> {
+        if raw.len() == EBCDIC_HEADER_SIZE && decode_textual_header(raw).text == text {
+            self.writer.write_all(raw)?;
+        } else {
+            let buffer = encode_textual_header(text, self.text_encoding)?;
+            self.writer.write_all(&buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an extended textual header record, see `write_text_record`.
+    fn write_extended_record(&mut self, text: &str, raw: &[u8]) -> Result<(), SegyError> {
+        if self.binary_header.is_none() || self.first_trace_offset.is_some() {
+            return Err(SegyError::ParseError(
+                "Extended textual headers go between the binary header and the traces".to_string(),
+            ));
+        }
+        self.write_text_record(text, raw)?;
+        self.extended_headers_written += 1;
+        Ok(())
+    }
+
+    /// Writes a data trailer record, see `write_text_record`.
+    fn write_trailer_text_record(&mut self, text: &str, raw: &[u8]) -> Result<(), SegyError> {
+        let is_rev2 = self
+            .binary_header
+            .as_ref()
+            .is_some_and(|binary_header| binary_header.segy_revision_major >= 2);
+        if !is_rev2 {
+            return Err(SegyError::ParseError(
+                "A data trailer needs a rev2 binary header".to_string(),
+            ));
+        }
+        self.start_traces()?;
+        self.write_text_record(text, raw)?;
+        self.trailer_records_written += 1;
+        Ok(())
+    }
+
+    /// Marks the end of the extended textual headers, returning the offset of
+    /// the first trace.
+    fn start_traces(&mut self) -> Result<u64, SegyError> {
+        match self.first_trace_offset {
+            Some(offset) => Ok(offset),
+            None => {
+                let offset = self.writer.stream_position()?;
+                self.first_trace_offset = Some(offset);
+                Ok(offset)
+            }
+        }
+    }
+
+    /// Writes a trace header, its extensions and its samples.
+    fn write_trace_parts(
+        &mut self,
+        header: &TraceHeader,
+        extension: Option<&TraceHeaderExtension1>,
+        samples: &TraceSamples,
+    ) -> Result<(), SegyError> {
+        let layout = self.layout.ok_or_else(|| {
+            SegyError::ParseError("Binary header has not been written".to_string())
+        })?;
+        if self.trailer_records_written > 0 {
+            return Err(SegyError::ParseError(
+                "Traces cannot follow the data trailer".to_string(),
+            ));
+        }
+        if extension.is_some() && layout.additional_trace_headers == 0 {
+            return Err(SegyError::ParseError(
+                "Binary header announces no trace header extensions".to_string(),
+            ));
+        }
+        let expected = layout.samples_in_trace(header) as usize;
+        if samples.len() != expected {
+            return Err(SegyError::ParseError(format!(
+                "Trace has {} samples but its header says {}",
+                samples.len(),
+                expected
+            )));
+        }
+
+        let header_buffer = encode_trace_header(header, self.endianness, &self.header_mapping)?;
+        let sample_buffer = samples.to_bytes_with_policy(
+            layout.sample_format,
+            self.endianness,
+            self.ibm_non_finite,
+        )?;
+        self.start_traces()?;
+        self.writer.write_all(&header_buffer)?;
+        if layout.additional_trace_headers > 0 {
+            let mut extension = match extension {
+                Some(extension) => extension.clone(),
+                None => TraceHeaderExtension1::from_trace_header(header),
+            };
+            extension.additional_trace_headers = layout.additional_trace_headers as u16;
+            let mut extensions_buffer = encode_trace_header_extension(&extension, self.endianness)?;
+            extensions_buffer.resize(layout.extensions_size(), 0);
+            self.writer.write_all(&extensions_buffer)?;
+        }
+        self.writer.write_all(&sample_buffer)?;
+        self.traces_written += 1;
+        self.variable_length_traces |= expected != layout.default_samples_per_trace as usize;
+        Ok(())
+    }
+}
+
+/// Encodes a 3200-byte textual header, padding `text` with spaces.
+pub(crate) fn encode_textual_header(
+    text: &str,
+    text_encoding: TextEncoding,
+) -> Result<Vec<u8>, SegyError> {
+    let bytes = text.as_bytes();
+    if bytes.len() > EBCDIC_HEADER_SIZE {
+        return Err(SegyError::ParseError(format!(
+            "Textual header is {} bytes long, at most {} fit",
+            bytes.len(),
+            EBCDIC_HEADER_SIZE
+        )));
+    }
+    let mut ascii_buffer = vec![b' '; EBCDIC_HEADER_SIZE];
+    ascii_buffer[..bytes.len()].copy_from_slice(bytes);
+
+    match text_encoding {
+        TextEncoding::Ascii => Ok(ascii_buffer),
+        TextEncoding::Ebcdic => {
+            let mut buffer = vec![0u8; EBCDIC_HEADER_SIZE];
+            Ebcdic::ascii_to_ebcdic(
+                &ascii_buffer,      // src
+                &mut buffer,        // dest
+                EBCDIC_HEADER_SIZE, // number of bytes to convert
+                true,               // lf_to_nel
+            );
+            Ok(buffer)
+        }
+    }
+}
+
+/// Encodes a binary header into 400 bytes in its own byte order.
+///
+/// A header read from a file is written over its raw bytes, rewriting only the
+/// fields whose value differs from what those bytes decode to.
+pub(crate) fn encode_binary_header(binary_header: &BinaryHeader) -> Result<Vec<u8>, SegyError> {
+    let encoded = encode_binary_header_fields(binary_header)?;
+    let Some(raw) = &binary_header.raw else {
+        return Ok(encoded);
+    };
+    // Raw bytes that no longer decode, e.g. after a change of byte order, are dropped.
+    let Ok(original) = parse_binary_header(&raw[..], binary_header.endianness) else {
+        return Ok(encoded);
+    };
+    let mut buffer = raw.to_vec();
+    let fields = binary_header_fields(&original)
+        .into_iter()
+        .zip(binary_header_fields(binary_header));
+    for ((_, byte, width, old_value), (_, _, _, new_value)) in fields {
+        if old_value != new_value {
+            let start = byte - EBCDIC_HEADER_SIZE - 1;
+            buffer[start..start + width].copy_from_slice(&encoded[start..start + width]);
+        }
+    }
+    Ok(buffer)
+}
+
+/// Encodes every field of a binary header into 400 bytes, leaving unassigned bytes zero.
+fn encode_binary_header_fields(binary_header: &BinaryHeader) -> Result<Vec<u8>, SegyError> {
+    let h = binary_header;
+    let e = h.endianness;
+    let mut buffer = vec![0u8; BINARY_HEADER_SIZE];
+    let b = &mut buffer;
+
+    write_i32(b, 0, h.job_id, e)?; // bytes 3201-3204
+    write_i32(b, 4, h.line_number, e)?; // bytes 3205-3208
+    write_i32(b, 8, h.reel_number, e)?; // bytes 3209-3212
+    write_u16(b, 12, h.data_traces_per_ensemble, e)?; // bytes 3213-3214
+    write_u16(b, 14, h.auxiliary_traces_per_ensemble, e)?; // bytes 3215-3216
+    write_u16(b, 16, h.sample_interval_us, e)?; // bytes 3217-3218
+    write_u16(b, 18, h.original_sample_interval_us, e)?; // bytes 3219-3220
+    write_u16(b, 20, h.samples_per_trace, e)?; // bytes 3221-3222
+    write_u16(b, 22, h.original_samples_per_trace, e)?; // bytes 3223-3224
+    write_u16(b, 24, h.sample_format_code.code(), e)?; // bytes 3225-3226
+    write_u16(b, 26, h.ensemble_fold, e)?; // bytes 3227-3228
+    write_i16(b, 28, h.trace_sorting_code, e)?; // bytes 3229-3230
+    write_u16(b, 30, h.vertical_sum_code, e)?; // bytes 3231-3232
+    write_u16(b, 32, h.sweep_frequency_start_hz, e)?; // bytes 3233-3234
+    write_u16(b, 34, h.sweep_frequency_end_hz, e)?; // bytes 3235-3236
+    write_u16(b, 36, h.sweep_length_ms, e)?; // bytes 3237-3238
+    write_u16(b, 38, h.sweep_type_code, e)?; // bytes 3239-3240
+    write_u16(b, 40, h.sweep_channel, e)?; // bytes 3241-3242
+    write_u16(b, 42, h.sweep_taper_start_ms, e)?; // bytes 3243-3244
+    write_u16(b, 44, h.sweep_taper_end_ms, e)?; // bytes 3245-3246
+    write_u16(b, 46, h.taper_type, e)?; // bytes 3247-3248
+    write_u16(b, 48, h.correlated_data_traces, e)?; // bytes 3249-3250
+    write_u16(b, 50, h.binary_gain_recovered, e)?; // bytes 3251-3252
+    write_u16(b, 52, h.amplitude_recovery_method, e)?; // bytes 3253-3254
+    write_u16(b, 54, h.measurement_system, e)?; // bytes 3255-3256
+    write_u16(b, 56, h.impulse_signal_polarity, e)?; // bytes 3257-3258
+    write_u16(b, 58, h.vibratory_polarity_code, e)?; // bytes 3259-3260
+
+    // SEG-Y rev2 extensions of the 16-bit fields above.
+    write_u32(b, 60, h.extended_data_traces_per_ensemble, e)?; // bytes 3261-3264
+    write_u32(b, 64, h.extended_auxiliary_traces_per_ensemble, e)?; // bytes 3265-3268
+    write_u32(b, 68, h.extended_samples_per_trace, e)?; // bytes 3269-3272
+    write_f64(b, 72, h.extended_sample_interval, e)?; // bytes 3273-3280
+    write_f64(b, 80, h.extended_original_sample_interval, e)?; // bytes 3281-3288
+    write_u32(b, 88, h.extended_original_samples_per_trace, e)?; // bytes 3289-3292
+    write_u32(b, 92, h.extended_ensemble_fold, e)?; // bytes 3293-3296
+    write_u32(b, 96, h.byte_order_constant, e)?; // bytes 3297-3300
+
+    b[300] = h.segy_revision_major; // byte 3501
+    b[301] = h.segy_revision_minor; // byte 3502
+    write_u16(b, 302, h.fixed_length_traces, e)?; // bytes 3503-3504
+    write_i16(b, 304, h.extended_textual_headers, e)?; // bytes 3505-3506
+    write_u32(b, 306, h.max_additional_trace_headers, e)?; // bytes 3507-3510
+    write_u16(b, 310, h.time_basis_code, e)?; // bytes 3511-3512
+    write_u64(b, 312, h.traces_in_file, e)?; // bytes 3513-3520
+    write_u64(b, 320, h.first_trace_offset, e)?; // bytes 3521-3528
+    write_i32(b, 328, h.trailer_stanza_records, e)?; // bytes 3529-3532
+
+    Ok(buffer)
+}
+
+/// Encodes a SEG-Y rev2 trace header extension 1 into 240 bytes, the inverse of
+/// `parse_trace_header_extension`.
+pub(crate) fn encode_trace_header_extension(
+    extension: &TraceHeaderExtension1,
+    endianness: Endianness,
+) -> Result<Vec<u8>, SegyError> {
+    let x = extension;
+    let e = endianness;
+    let mut buffer = vec![0u8; TRACE_HEADER_SIZE];
+    let b = &mut buffer;
+
+    write_u64(b, 0, x.trace_sequence_line, e)?; // bytes 1-8
+    write_u64(b, 8, x.trace_sequence_file, e)?; // bytes 9-16
+    write_i64(b, 16, x.field_record_number, e)?; // bytes 17-24
+    write_i64(b, 24, x.ensemble_number, e)?; // bytes 25-32
+    write_f64(b, 32, x.receiver_group_elevation, e)?; // bytes 33-40
+    write_f64(b, 40, x.receiver_group_depth, e)?; // bytes 41-48
+    write_f64(b, 48, x.source_surface_elevation, e)?; // bytes 49-56
+    write_f64(b, 56, x.source_depth, e)?; // bytes 57-64
+    write_f64(b, 64, x.receiver_datum_elevation, e)?; // bytes 65-72
+    write_f64(b, 72, x.source_datum_elevation, e)?; // bytes 73-80
+    write_f64(b, 80, x.source_water_depth, e)?; // bytes 81-88
+    write_f64(b, 88, x.group_water_depth, e)?; // bytes 89-96
+    write_f64(b, 96, x.source_x, e)?; // bytes 97-104
+    write_f64(b, 104, x.source_y, e)?; // bytes 105-112
+    write_f64(b, 112, x.group_x, e)?; // bytes 113-120
+    write_f64(b, 120, x.group_y, e)?; // bytes 121-128
+    write_f64(b, 128, x.offset, e)?; // bytes 129-136
+    write_u32(b, 136, x.trace_sample_count, e)?; // bytes 137-140
+    write_i32(b, 140, x.nanoseconds, e)?; // bytes 141-144
+    write_f64(b, 144, x.sample_interval, e)?; // bytes 145-152
+    write_i32(b, 152, x.cable_number, e)?; // bytes 153-156
+    write_u16(b, 156, x.additional_trace_headers, e)?; // bytes 157-158
+    write_i16(b, 158, x.last_trace_flag, e)?; // bytes 159-160
+    write_f64(b, 160, x.cdp_x, e)?; // bytes 161-168
+    write_f64(b, 168, x.cdp_y, e)?; // bytes 169-176
+    b[232..240].copy_from_slice(EXTENSION_1_NAME); // bytes 233-240
+
+    Ok(buffer)
+}
+
+/// Encodes a trace header into 240 bytes, the inverse of `parse_trace_header`.
+///
+/// A header that kept its raw bytes is written over them, rewriting only the
+/// fields whose value differs from what those bytes decode to, at the locations
+/// given by `mapping`. Otherwise the in-line, cross-line and CDP X/Y numbers are
+/// written to their SEG-Y rev1 locations and then to the locations given by
+/// `mapping`, which take precedence where they overlap other fields.
+pub(crate) fn encode_trace_header(
+    header: &TraceHeader,
+    endianness: Endianness,
+    mapping: &HeaderMapping,
+) -> Result<Vec<u8>, SegyError> {
+    let Some(raw) = &header.raw else {
+        return encode_trace_header_fields(header, endianness, mapping);
+    };
+    let original = parse_trace_header(&raw[..], endianness, mapping)?;
+    let mut buffer = raw.to_vec();
+    for field in TRACE_HEADER_FIELDS {
+        let value = field.value(header);
+        if value != field.value(&original) {
+            field
+                .location(mapping)
+                .write(&mut buffer, value, endianness)?;
+        }
+    }
+    Ok(buffer)
+}
+
+/// Encodes every field of a trace header into 240 bytes, leaving unassigned bytes zero.
+fn encode_trace_header_fields(
+    header: &TraceHeader,
+    endianness: Endianness,
+    mapping: &HeaderMapping,
+) -> Result<Vec<u8>, SegyError> {
+    let h = header;
+    let e = endianness;
+    let mut buffer = vec![0u8; TRACE_HEADER_SIZE];
+    let b = &mut buffer;
+
+    write_i32(b, 0, h.trace_sequence_line, e)?; // bytes 1-4
+    write_i32(b, 4, h.trace_sequence_file, e)?; // bytes 5-8
+    write_i32(b, 8, h.field_record_number, e)?; // bytes 9-12
+    write_i32(b, 12, h.trace_number, e)?; // bytes 13-16
+    write_i32(b, 16, h.source_point_number, e)?; // bytes 17-20
+    write_i32(b, 20, h.ensemble_number, e)?; // bytes 21-24
+    write_i32(b, 24, h.trace_in_ensemble, e)?; // bytes 25-28
+    write_i16(b, 28, h.trace_identification_code, e)?; // bytes 29-30
+    write_i16(b, 30, h.vertically_summed_traces, e)?; // bytes 31-32
+    write_i16(b, 32, h.horizontally_stacked_traces, e)?; // bytes 33-34
+    write_i16(b, 34, h.data_use, e)?; // bytes 35-36
+    write_i32(b, 36, h.offset, e)?; // bytes 37-40
+    write_i32(b, 40, h.receiver_group_elevation, e)?; // bytes 41-44
+    write_i32(b, 44, h.source_surface_elevation, e)?; // bytes 45-48
+    write_i32(b, 48, h.source_depth, e)?; // bytes 49-52
+    write_i32(b, 52, h.receiver_datum_elevation, e)?; // bytes 53-56
+    write_i32(b, 56, h.source_datum_elevation, e)?; // bytes 57-60
+    write_i32(b, 60, h.source_water_depth, e)?; // bytes 61-64
+    write_i32(b, 64, h.group_water_depth, e)?; // bytes 65-68
+    write_i16(b, 68, h.elevation_scalar, e)?; // bytes 69-70
+    write_i16(b, 70, h.coord_scalar, e)?; // bytes 71-72
+    write_i32(b, 72, h.source_x, e)?; // bytes 73-76
+    write_i32(b, 76, h.source_y, e)?; // bytes 77-80
+    write_i32(b, 80, h.group_x, e)?; // bytes 81-84
+    write_i32(b, 84, h.group_y, e)?; // bytes 85-88
+    write_i16(b, 88, h.coordinate_units, e)?; // bytes 89-90
+    write_i16(b, 90, h.weathering_velocity, e)?; // bytes 91-92
+    write_i16(b, 92, h.subweathering_velocity, e)?; // bytes 93-94
+    write_i16(b, 94, h.source_uphole_time_ms, e)?; // bytes 95-96
+    write_i16(b, 96, h.group_uphole_time_ms, e)?; // bytes 97-98
+    write_i16(b, 98, h.source_static_correction_ms, e)?; // bytes 99-100
+    write_i16(b, 100, h.group_static_correction_ms, e)?; // bytes 101-102
+    write_i16(b, 102, h.total_static_applied_ms, e)?; // bytes 103-104
+    write_i16(b, 104, h.lag_time_a_ms, e)?; // bytes 105-106
+    write_i16(b, 106, h.lag_time_b_ms, e)?; // bytes 107-108
+    write_i16(b, 108, h.delay_recording_time_ms, e)?; // bytes 109-110
+    write_i16(b, 110, h.mute_time_start_ms, e)?; // bytes 111-112
+    write_i16(b, 112, h.mute_time_end_ms, e)?; // bytes 113-114
+    write_u16(b, 114, h.trace_sample_count, e)?; // bytes 115-116
+    write_u16(b, 116, h.trace_sample_interval_us, e)?; // bytes 117-118
+    write_i16(b, 118, h.gain_type, e)?; // bytes 119-120
+    write_i16(b, 120, h.instrument_gain_constant_db, e)?; // bytes 121-122
+    write_i16(b, 122, h.instrument_early_gain_db, e)?; // bytes 123-124
+    write_i16(b, 124, h.correlated, e)?; // bytes 125-126
+    write_i16(b, 126, h.sweep_frequency_start_hz, e)?; // bytes 127-128
+    write_i16(b, 128, h.sweep_frequency_end_hz, e)?; // bytes 129-130
+    write_i16(b, 130, h.sweep_length_ms, e)?; // bytes 131-132
+    write_i16(b, 132, h.sweep_type, e)?; // bytes 133-134
+    write_i16(b, 134, h.sweep_taper_start_ms, e)?; // bytes 135-136
+    write_i16(b, 136, h.sweep_taper_end_ms, e)?; // bytes 137-138
+    write_i16(b, 138, h.taper_type, e)?; // bytes 139-140
+    write_i16(b, 140, h.alias_filter_frequency_hz, e)?; // bytes 141-142
+    write_i16(b, 142, h.alias_filter_slope, e)?; // bytes 143-144
+    write_i16(b, 144, h.notch_filter_frequency_hz, e)?; // bytes 145-146
+    write_i16(b, 146, h.notch_filter_slope, e)?; // bytes 147-148
+    write_i16(b, 148, h.low_cut_frequency_hz, e)?; // bytes 149-150
+    write_i16(b, 150, h.high_cut_frequency_hz, e)?; // bytes 151-152
+    write_i16(b, 152, h.low_cut_slope, e)?; // bytes 153-154
+    write_i16(b, 154, h.high_cut_slope, e)?; // bytes 155-156
+    write_u16(b, 156, h.year_data_recorded, e)?; // bytes 157-158
+    write_u16(b, 158, h.day_of_year, e)?; // bytes 159-160
+    write_u16(b, 160, h.hour_of_day, e)?; // bytes 161-162
+    write_u16(b, 162, h.minute_of_hour, e)?; // bytes 163-164
+    write_u16(b, 164, h.second_of_minute, e)?; // bytes 165-166
+    write_i16(b, 166, h.time_basis_code, e)?; // bytes 167-168
+    write_i16(b, 168, h.trace_weighting_factor, e)?; // bytes 169-170
+    write_i16(b, 170, h.geophone_group_roll_switch, e)?; // bytes 171-172
+    write_i16(b, 172, h.geophone_group_first_trace, e)?; // bytes 173-174
+    write_i16(b, 174, h.geophone_group_last_trace, e)?; // bytes 175-176
+    write_i16(b, 176, h.gap_size, e)?; // bytes 177-178
+    write_i16(b, 178, h.over_travel, e)?; // bytes 179-180
+    write_i32(b, 180, h.cdp_x, e)?; // bytes 181-184
+    write_i32(b, 184, h.cdp_y, e)?; // bytes 185-188
+    write_i32(b, 188, h.inline_number, e)?; // bytes 189-192
+    write_i32(b, 192, h.crossline_number, e)?; // bytes 193-196
+    write_i32(b, 196, h.shotpoint_number, e)?; // bytes 197-200
+    write_i16(b, 200, h.shotpoint_scalar, e)?; // bytes 201-202
+    write_i16(b, 202, h.trace_value_measurement_unit, e)?; // bytes 203-204
+    write_i32(b, 204, h.transduction_constant_mantissa, e)?; // bytes 205-208
+    write_i16(b, 208, h.transduction_constant_exponent, e)?; // bytes 209-210
+    write_i16(b, 210, h.transduction_units, e)?; // bytes 211-212
+    write_i16(b, 212, h.device_identifier, e)?; // bytes 213-214
+    write_i16(b, 214, h.time_scalar, e)?; // bytes 215-216
+    write_i16(b, 216, h.source_type_orientation, e)?; // bytes 217-218
+    write_i32(b, 218, h.source_energy_direction_mantissa, e)?; // bytes 219-222
+    write_i16(b, 222, h.source_energy_direction_exponent, e)?; // bytes 223-224
+    write_i32(b, 224, h.source_measurement_mantissa, e)?; // bytes 225-228
+    write_i16(b, 228, h.source_measurement_exponent, e)?; // bytes 229-230
+    write_i16(b, 230, h.source_measurement_unit, e)?; // bytes 231-232
+
+    mapping.write(header, b, endianness)?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use super::*;
+    use crate::{
+        format::SampleFormat, read_segy_from_file, stanza::parse_stanzas, testing::temp_file,
+        write_segy_to_file,
+    };
+
+    const SAMPLE_FORMATS: [SampleFormat; 14] = [
+        SampleFormat::IbmFloat,
+        SampleFormat::Int32,
+        SampleFormat::Int16,
+        SampleFormat::FixedPointWithGain,
+        SampleFormat::IeeeFloat,
+        SampleFormat::IeeeDouble,
+        SampleFormat::Int24,
+        SampleFormat::Int8,
+        SampleFormat::Int64,
+        SampleFormat::UInt32,
+        SampleFormat::UInt16,
+        SampleFormat::UInt64,
+        SampleFormat::UInt24,
+        SampleFormat::UInt8,
+    ];
+
+    /// Traces whose samples are small whole numbers, exact in every sample format.
+    fn traces() -> Vec<Trace> {
+        (0..5)
+            .map(|index| Trace {
+                header: TraceHeader {
+                    trace_sequence_line: index + 1,
+                    trace_sequence_file: index + 1,
+                    inline_number: 10 + index / 2,
+                    crossline_number: -20 - index,
+                    cdp_x: 500_000 + index * 25,
+                    trace_sample_count: 6,
+                    ..Default::default()
+                },
+                data_samples: (0..6)
+                    .map(|sample| ((index * 7 + sample) % 100) as f32)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Writes `traces` with `SegyWriter` to a temporary file.
+    fn write(
+        sample_format: SampleFormat,
+        endianness: Endianness,
+        text_encoding: TextEncoding,
+        traces: &[Trace],
+    ) -> std::path::PathBuf {
+        let path = temp_file("round-trip.sgy", &[]);
+        let mut binary_header = BinaryHeader::new(sample_format, 6, 4000);
+        binary_header.endianness = endianness;
+        let mut writer = SegyWriter::new(File::create(&path).unwrap());
+        writer.set_text_encoding(text_encoding);
+        writer.write_textual_header("C 1 ROUND TRIP").unwrap();
+        writer.write_binary_header(&binary_header).unwrap();
+        writer
+            .write_extended_textual_header("C 2 EXTENDED")
+            .unwrap();
+        for trace in traces {
+            writer.write_trace(trace).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn files_written_are_read_back() {
+        let traces = traces();
+        for sample_format in SAMPLE_FORMATS {
+            for endianness in [Endianness::Big, Endianness::Little] {
+                for text_encoding in [TextEncoding::Ebcdic, TextEncoding::Ascii] {
+                    let case = format!("{:?} {:?} {:?}", sample_format, endianness, text_encoding);
+                    let path = write(sample_format, endianness, text_encoding, &traces);
+                    let segy_file = read_segy_from_file(&path).unwrap();
+
+                    assert!(
+                        segy_file.ebcdic_header.starts_with("C 1 ROUND TRIP"),
+                        "{}",
+                        case
+                    );
+                    assert_eq!(segy_file.extended_textual_headers.len(), 1, "{}", case);
+                    assert!(
+                        segy_file.extended_textual_headers[0]
+                            .text
+                            .starts_with("C 2 EXTENDED"),
+                        "{}",
+                        case
+                    );
+                    let binary_header = &segy_file.binary_header;
+                    assert_eq!(binary_header.sample_format_code, sample_format, "{}", case);
+                    assert_eq!(binary_header.endianness, endianness, "{}", case);
+                    assert_eq!(binary_header.samples_per_trace, 6, "{}", case);
+                    assert_eq!(binary_header.sample_interval_us, 4000, "{}", case);
+                    assert_eq!(binary_header.extended_textual_headers, 1, "{}", case);
+                    assert_eq!(segy_file.traces, traces, "{}", case);
+
+                    // Writing back what was read reproduces the file.
+                    let copy = temp_file("round-trip-copy.sgy", &[]);
+                    write_segy_to_file(&copy, &segy_file).unwrap();
+                    assert_eq!(
+                        std::fs::read(&copy).unwrap(),
+                        std::fs::read(&path).unwrap(),
+                        "{}",
+                        case
+                    );
+                    std::fs::remove_file(copy).unwrap();
+                    std::fs::remove_file(path).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_length_flag_is_cleared_for_traces_of_differing_lengths() {
+        let traces = traces();
+        let path = write(
+            SampleFormat::IeeeFloat,
+            Endianness::Big,
+            TextEncoding::Ebcdic,
+            &traces,
+        );
+        assert_eq!(
+            read_segy_from_file(&path)
+                .unwrap()
+                .binary_header
+                .fixed_length_traces,
+            1
+        );
+        std::fs::remove_file(path).unwrap();
+
+        // The second trace is longer than the binary header says.
+        let mut traces = traces;
+        traces[1].data_samples.extend([1.0, 2.0]);
+        traces[1].header.trace_sample_count = 8;
+        let path = write(
+            SampleFormat::IeeeFloat,
+            Endianness::Big,
+            TextEncoding::Ebcdic,
+            &traces,
+        );
+        let segy_file = read_segy_from_file(&path).unwrap();
+        assert_eq!(segy_file.binary_header.fixed_length_traces, 0);
+        assert_eq!(segy_file.traces, traces);
+
+        let mut reader = crate::open_segy_file(&path).unwrap();
+        assert_eq!(reader.fixed_trace_size().unwrap(), None);
+        assert_eq!(reader.trace(4).unwrap(), traces[4]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rev2_files_are_read_back() {
+        let traces = traces();
+        let extension = TraceHeaderExtension1 {
+            trace_sequence_file: 1,
+            source_x: 123_456.75,
+            cdp_x: 500_000.5,
+            additional_trace_headers: 1,
+            ..Default::default()
+        };
+        let location = TextStanza::new("SEG: Location Data ver 1.0", &[("Datum", "WGS 84")]);
+        let processing = TextStanza::new("Processing", &[("Step", "Converted")]);
+        for endianness in [Endianness::Big, Endianness::Little] {
+            let path = temp_file("rev2-round-trip.sgy", &[]);
+            let mut binary_header = BinaryHeader::new(SampleFormat::IeeeFloat, 6, 4000);
+            binary_header.endianness = endianness;
+            let mut writer = SegyWriter::new(File::create(&path).unwrap());
+            writer.set_revision(SegyRevision::Rev2);
+            writer.write_textual_header("C 1 REV2").unwrap();
+            writer.write_binary_header(&binary_header).unwrap();
+            writer
+                .write_extended_stanzas(std::slice::from_ref(&location))
+                .unwrap();
+            writer
+                .write_trace_with_extension(&traces[0], &extension)
+                .unwrap();
+            for trace in &traces[1..] {
+                writer.write_trace(trace).unwrap();
+            }
+            writer
+                .write_trailer_stanzas(&[processing.clone(), TextStanza::end_text()])
+                .unwrap();
+            writer.finish().unwrap();
+
+            let bytes = std::fs::read(&path).unwrap();
+            let u32_bytes = |value: u32| match endianness {
+                Endianness::Big => value.to_be_bytes(),
+                Endianness::Little => value.to_le_bytes(),
+            };
+            assert_eq!(bytes[3296..3300], u32_bytes(0x0102_0304)); // bytes 3297-3300
+            assert_eq!(bytes[3268..3272], u32_bytes(6)); // bytes 3269-3272
+            assert_eq!(bytes[3500..3502], [2, 0]); // bytes 3501-3502
+                                                   // The location stanza and the EndText stanza each fill a record.
+            let first_trace_offset = 3600 + 2 * 3200;
+            let trace_size = 240 + 240 + 6 * 4;
+            let extension_name = first_trace_offset + 240 + 232;
+            assert_eq!(&bytes[extension_name..extension_name + 8], b"SEG00001"); // bytes 233-240
+            assert_eq!(bytes.len(), first_trace_offset + 5 * trace_size + 2 * 3200);
+
+            let mut reader = crate::open_segy_file(&path).unwrap();
+            let binary_header = reader.binary_header().unwrap().clone();
+            assert_eq!(binary_header.endianness, endianness);
+            assert_eq!(binary_header.byte_order_constant, 0x0102_0304);
+            assert_eq!(binary_header.segy_revision_major, 2);
+            assert_eq!(binary_header.segy_revision_minor, 0);
+            assert_eq!(binary_header.extended_samples_per_trace, 6);
+            assert_eq!(binary_header.extended_textual_headers, 2);
+            assert_eq!(binary_header.max_additional_trace_headers, 1);
+            assert_eq!(binary_header.traces_in_file, 5);
+            assert_eq!(binary_header.first_trace_offset, first_trace_offset as u64);
+            assert_eq!(binary_header.trailer_stanza_records, 2);
+
+            let extended_textual_headers = reader.extended_textual_headers();
+            assert!(extended_textual_headers[0]
+                .text
+                .starts_with("((SEG: Location Data ver 1.0))"));
+            assert!(extended_textual_headers[1]
+                .text
+                .starts_with("((SEG: EndText))"));
+            assert_eq!(
+                parse_stanzas(extended_textual_headers),
+                [location.clone(), TextStanza::end_text()]
+            );
+
+            for (index, trace) in traces.iter().enumerate() {
+                assert_eq!(reader.trace(index).unwrap(), *trace);
+            }
+            assert_eq!(
+                reader.trace_header_extension(0).unwrap(),
+                Some(extension.clone())
+            );
+            assert_eq!(
+                reader.trace_header_extension(3).unwrap(),
+                Some(TraceHeaderExtension1::from_trace_header(&traces[3].header))
+            );
+
+            // The EndText stanza starts a trailer record of its own too.
+            let trailer = reader.read_trailer_records().unwrap();
+            assert_eq!(trailer.len(), 2);
+            assert!(trailer[1].text.starts_with("((SEG: EndText))"));
+            assert_eq!(
+                parse_stanzas(&trailer),
+                [processing.clone(), TextStanza::end_text()]
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}